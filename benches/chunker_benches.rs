@@ -6,6 +6,13 @@ use criterion::{
 };
 use criterion::measurement::WallTime;
 
+use chunker_benches::{
+    AEConfig, BorgConfig, BorgParams, BuzhashConfig, CasyncConfig, chunk_parallel,
+    ChunkSizeParms, Chunker, DEFAULT_BUZHASH_SEED, FastCDCConfig, FixedSizeConfig, FP_POLY,
+    GearConfig, JumpConfig, MaxpConfig, Normalization, RabinCDC, RabinConfig, RabinTables,
+    RamConfig, ResticConfig, RonomonConfig, RsyncConfig, TttdConfig, V2020Config,
+};
+
 /// Constant representing a kilobyte in bytes
 const KB: usize = 1024;
 /// Constant representing a megabyte in bytes
@@ -17,6 +24,21 @@ const BENCH_MIN_CHUNK_SIZE: usize = 8 * KB;
 const BENCH_AVG_CHUNK_SIZE: usize = 10 * KB;
 /// Maximum chunk size used in the benchmarks
 const BENCH_MAX_CHUNK_SIZE: usize = 64 * KB;
+/// Window size used by the rolling hash benchmarks
+const BENCH_WIN_SIZE: usize = 64;
+/// Jump length used by the jump-ahead benchmarks
+const BENCH_JUMP_LEN: usize = KB;
+/// Number of zero fingerprint bits at a cut point of the restic benchmarks, which gives the
+/// average chunk size used in the benchmarks
+const BENCH_AVERAGE_BITS: u32 = 11;
+/// Borg chunker parameters with the minimum and maximum chunk sizes used in the benchmarks, borg
+/// requires at least as many mask bits as the exponent of the minimum chunk size
+const BENCH_BORG_PARAMS: BorgParams = BorgParams {
+    min_exp: 13,
+    max_exp: 16,
+    mask_bits: 13,
+    window_size: 4095,
+};
 
 /// Seed used for benchmark data generation
 const SEED: u128 = 0xDEADBEEFCAFEF00DC0DEFACE99C0FFEEu128;
@@ -27,7 +49,21 @@ const BENCH_DATA_SIZE: usize = 40 * MB;
 enum Algorithm {
     Fixedsize,
     GearCDC,
+    NativeGearCDC,
     FastCDC,
+    NativeFastCDC,
+    ScalarFastCDC,
+    BuzhashCDC,
+    AeCDC,
+    RamCDC,
+    RsyncCDC,
+    JumpCDC,
+    RabinCDC,
+    TttdCDC,
+    MaxpCDC,
+    ResticCDC,
+    BorgCDC,
+    CasyncCDC,
 }
 
 impl Display for Algorithm {
@@ -35,121 +71,303 @@ impl Display for Algorithm {
         let str = match self {
             Algorithm::Fixedsize => "Fixed Size Chunking",
             Algorithm::GearCDC => "Gear Content Defined Chunking",
+            Algorithm::NativeGearCDC => "Native Gear Content Defined Chunking",
             Algorithm::FastCDC => "Fast Content Defined Chunking",
+            Algorithm::NativeFastCDC => "Native Fast Content Defined Chunking",
+            Algorithm::ScalarFastCDC => "Scalar Fast Content Defined Chunking",
+            Algorithm::BuzhashCDC => "Buzhash Content Defined Chunking",
+            Algorithm::AeCDC => "Asymmetric Extremum Content Defined Chunking",
+            Algorithm::RamCDC => "Rapid Asymmetric Maximum Chunking",
+            Algorithm::RsyncCDC => "Rsync Content Defined Chunking",
+            Algorithm::JumpCDC => "Jump Content Defined Chunking",
+            Algorithm::RabinCDC => "Rabin Content Defined Chunking",
+            Algorithm::TttdCDC => "Two Thresholds Two Divisors Content Defined Chunking",
+            Algorithm::MaxpCDC => "Local Maximum Content Defined Chunking",
+            Algorithm::ResticCDC => "Restic Content Defined Chunking",
+            Algorithm::BorgCDC => "Borg Content Defined Chunking",
+            Algorithm::CasyncCDC => "Casync Content Defined Chunking",
         };
         write!(f, "{}", str)
     }
 }
 
+/// Chunking algorithms run by the throughput benchmarks
+const ALGORITHMS: [Algorithm; 17] = [
+    Algorithm::Fixedsize,
+    Algorithm::GearCDC,
+    Algorithm::NativeGearCDC,
+    Algorithm::FastCDC,
+    Algorithm::NativeFastCDC,
+    Algorithm::ScalarFastCDC,
+    Algorithm::BuzhashCDC,
+    Algorithm::AeCDC,
+    Algorithm::RamCDC,
+    Algorithm::RsyncCDC,
+    Algorithm::JumpCDC,
+    Algorithm::RabinCDC,
+    Algorithm::TttdCDC,
+    Algorithm::MaxpCDC,
+    Algorithm::ResticCDC,
+    Algorithm::BorgCDC,
+    Algorithm::CasyncCDC,
+];
+
+/// Input sizes of the Rabin construction benchmarks
+const BENCH_SMALL_SIZES: [usize; 3] = [KB, 16 * KB, 256 * KB];
+
+/// Thread counts the parallel chunking benchmarks scale across
+const BENCH_THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// Chunk size parameters used in the benchmarks
+const BENCH_CHUNK_PARMS: ChunkSizeParms = ChunkSizeParms {
+    min_chunk_size: BENCH_MIN_CHUNK_SIZE,
+    avg_chunk_size: BENCH_AVG_CHUNK_SIZE,
+    max_chunk_size: BENCH_MAX_CHUNK_SIZE,
+};
+
 /// Run the specified chunking algorithm on the provided data.
 ///
 /// # Arguments
 ///
+/// * `group` - The benchmark group to add the benchmark to.
 /// * `algo` - The chunking algorithm to use.
-/// * `data` - The data to hash.
-fn run_chunking_algorithm(group: &mut BenchmarkGroup<WallTime>, algo: &Algorithm, data: &[u8]) {
+/// * `data` - The data to chunk.
+/// * `threads` - Number of threads to chunk on, or `None` to chunk serially.
+fn run_chunking_algorithm(
+    group: &mut BenchmarkGroup<WallTime>,
+    algo: &Algorithm,
+    data: &[u8],
+    threads: Option<usize>,
+) {
+    let name = match threads {
+        Some(threads) => format!("{}/{} threads", algo, threads),
+        None => algo.to_string(),
+    };
     match algo {
         Algorithm::Fixedsize => {
-            run_fsc(group, &algo.to_string(), data);
+            run_chunker(
+                group,
+                &name,
+                &FixedSizeConfig::new(BENCH_AVG_CHUNK_SIZE),
+                data,
+                threads,
+            );
         }
         Algorithm::GearCDC => {
-            run_gearcdc(group, &algo.to_string(), data);
+            run_chunker(group, &name, &RonomonConfig::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::NativeGearCDC => {
+            run_chunker(group, &name, &GearConfig::new(BENCH_CHUNK_PARMS), data, threads);
         }
         Algorithm::FastCDC => {
-            run_fastcdc(group, &algo.to_string(), data);
+            run_chunker(group, &name, &V2020Config::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::NativeFastCDC => {
+            let config = FastCDCConfig::new(BENCH_CHUNK_PARMS, Normalization::Level1);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::ScalarFastCDC => {
+            let config = FastCDCConfig {
+                vectorized: false,
+                ..FastCDCConfig::new(BENCH_CHUNK_PARMS, Normalization::Level1)
+            };
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::BuzhashCDC => {
+            let config =
+                BuzhashConfig::new(BENCH_WIN_SIZE, DEFAULT_BUZHASH_SEED, BENCH_CHUNK_PARMS);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::AeCDC => {
+            run_chunker(group, &name, &AEConfig::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::RamCDC => {
+            run_chunker(group, &name, &RamConfig::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::RsyncCDC => {
+            let config = RsyncConfig::new(BENCH_WIN_SIZE, BENCH_CHUNK_PARMS);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::JumpCDC => {
+            let config = JumpConfig::new(BENCH_JUMP_LEN, BENCH_CHUNK_PARMS);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::RabinCDC => {
+            let config = RabinConfig::new(BENCH_WIN_SIZE, BENCH_CHUNK_PARMS);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::TttdCDC => {
+            run_chunker(group, &name, &TttdConfig::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::MaxpCDC => {
+            run_chunker(group, &name, &MaxpConfig::new(BENCH_CHUNK_PARMS), data, threads);
+        }
+        Algorithm::ResticCDC => {
+            let config = ResticConfig::with_boundaries(
+                FP_POLY,
+                BENCH_AVERAGE_BITS,
+                BENCH_MIN_CHUNK_SIZE,
+                BENCH_MAX_CHUNK_SIZE,
+            );
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::BorgCDC => {
+            let config = BorgConfig::new(0, BENCH_BORG_PARAMS);
+            run_chunker(group, &name, &config, data, threads);
+        }
+        Algorithm::CasyncCDC => {
+            run_chunker(group, &name, &CasyncConfig::new(BENCH_CHUNK_PARMS), data, threads);
         }
     }
 }
 
-/// Run the FixedSizeChunking algorithm on the provided data.
+/// Run a chunker on the provided data.
 ///
 /// # Arguments
 ///
 /// * `group` - The benchmark group to add the benchmark to.
 /// * `name` - The name of the benchmark.
+/// * `chunker` - The configured chunking algorithm.
 /// * `data` - The data to chunk.
-fn run_fsc(group: &mut BenchmarkGroup<WallTime>, name: &str, data: &[u8]) {
+/// * `threads` - Number of threads to chunk on, or `None` to chunk serially.
+fn run_chunker<C: Chunker + Sync>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    chunker: &C,
+    data: &[u8],
+    threads: Option<usize>,
+) {
     group.bench_function(name, |b| {
         b.iter(|| {
-            let chunks: Vec<_> = chunker_benches::FixedSizeChunking::new(
-                black_box(data),
-                black_box(BENCH_AVG_CHUNK_SIZE),
-            )
-            .collect();
+            let chunks: Vec<_> = match threads {
+                Some(threads) => chunk_parallel(chunker, black_box(data), threads),
+                None => chunker.chunks(black_box(data)).collect(),
+            };
             black_box(chunks);
         })
     });
 }
 
-/// Run the GearCDC algorithm on the provided data.
+/// Main function for running the chunking algorithm benchmarks.
+///
+/// This function benchmarks the throughput of various chunking algorithms using the
+/// generated data block.
 ///
 /// # Arguments
 ///
-/// * `group` - The benchmark group to add the benchmark to.
-/// * `name` - The name of the benchmark.
-/// * `data` - The data to chunk.
-fn run_gearcdc(group: &mut BenchmarkGroup<WallTime>, name: &str, data: &[u8]) {
-    group.bench_function(name, |b| {
-        b.iter(|| {
-            let chunks: Vec<_> = fastcdc::ronomon::FastCDC::new(
-                black_box(data),
-                black_box(BENCH_MIN_CHUNK_SIZE),
-                black_box(BENCH_AVG_CHUNK_SIZE),
-                black_box(BENCH_MAX_CHUNK_SIZE),
-            )
-            .collect();
-            black_box(chunks);
-        })
+/// * `c` - The criterion context used for benchmarking.
+fn run_benchmark(c: &mut Criterion) {
+    // Generate a data block for benchmarking
+    let data_block = chunker_benches::generate_data_block(BENCH_DATA_SIZE, Some(SEED));
+
+    // Benchmark the throughput of the various chunking algorithms
+    let mut group = c.benchmark_group("chunkers-throughput");
+    group.throughput(Throughput::Bytes(BENCH_DATA_SIZE as u64));
+    group.measurement_time(Duration::from_secs(10));
+
+    // Run the chunking algorithms
+    ALGORITHMS.iter().for_each(|algo| {
+        run_chunking_algorithm(&mut group, algo, &data_block, None);
     });
 }
 
-/// Run the FastCDC algorithm on the provided data.
+/// Benchmark comparing the jump-ahead chunker with `fastcdc::v2020`.
+///
+/// Both chunkers run in their own group, so the report shows the throughput of the jump-ahead
+/// chunker relative to `fastcdc::v2020` for a few jump lengths.
 ///
 /// # Arguments
 ///
-/// * `group` - The benchmark group to add the benchmark to.
-/// * `name` - The name of the benchmark.
-/// * `data` - The data to chunk.
-fn run_fastcdc(group: &mut BenchmarkGroup<WallTime>, name: &str, data: &[u8]) {
-    group.bench_function(name, |b| {
-        b.iter(|| {
-            let chunks: Vec<_> = fastcdc::v2020::FastCDC::new(
-                black_box(data),
-                black_box(BENCH_MIN_CHUNK_SIZE as u32),
-                black_box(BENCH_AVG_CHUNK_SIZE as u32),
-                black_box(BENCH_MAX_CHUNK_SIZE as u32),
-            )
-            .collect();
-            black_box(chunks);
-        })
-    });
+/// * `c` - The criterion context used for benchmarking.
+fn run_jump_benchmark(c: &mut Criterion) {
+    let data_block = chunker_benches::generate_data_block(BENCH_DATA_SIZE, Some(SEED));
+
+    let mut group = c.benchmark_group("jump-vs-fastcdc-v2020");
+    group.throughput(Throughput::Bytes(BENCH_DATA_SIZE as u64));
+    group.measurement_time(Duration::from_secs(10));
+
+    run_chunking_algorithm(&mut group, &Algorithm::FastCDC, &data_block, None);
+    for jump_len in [BENCH_JUMP_LEN / 2, BENCH_JUMP_LEN, BENCH_JUMP_LEN * 3 / 2] {
+        let name = format!("{} J{}", Algorithm::JumpCDC, jump_len);
+        let config = JumpConfig::new(jump_len, BENCH_CHUNK_PARMS);
+        run_chunker(&mut group, &name, &config, &data_block, None);
+    }
 }
 
-/// Main function for running the chunking algorithm benchmarks.
+/// Benchmark of chunking a single buffer on several threads.
 ///
-/// This function benchmarks the throughput of various chunking algorithms using the
-/// generated data block.
+/// Every algorithm is run with each of the thread counts, so the report shows how the throughput
+/// scales with the number of threads.
 ///
 /// # Arguments
 ///
 /// * `c` - The criterion context used for benchmarking.
-fn run_benchmark(c: &mut Criterion) {
-    // Generate a data block for benchmarking
+fn run_parallel_benchmark(c: &mut Criterion) {
     let data_block = chunker_benches::generate_data_block(BENCH_DATA_SIZE, Some(SEED));
 
-    // Benchmark the throughput of the various chunking algorithms
-    let mut group = c.benchmark_group("chunkers-throughput");
+    let mut group = c.benchmark_group("parallel-scaling");
     group.throughput(Throughput::Bytes(BENCH_DATA_SIZE as u64));
-    group.measurement_time(Duration::from_secs(10));
+    group.measurement_time(Duration::from_secs(5));
+
+    for algo in &ALGORITHMS {
+        for threads in BENCH_THREADS {
+            run_chunking_algorithm(&mut group, algo, &data_block, Some(threads));
+        }
+    }
+}
+
+/// Benchmark of the construction cost of the Rabin chunker on small inputs.
+///
+/// Every input is chunked by a chunker building its own tables and by one borrowing tables built
+/// ahead, so the report shows how much of the time goes into building the tables.
+///
+/// # Arguments
+///
+/// * `c` - The criterion context used for benchmarking.
+fn run_rabin_construction_benchmark(c: &mut Criterion) {
+    let data_block = chunker_benches::generate_data_block(BENCH_SMALL_SIZES[2], Some(SEED));
+    let shared = RabinTables::new(BENCH_WIN_SIZE);
 
-    // Define the chunking algorithms to benchmark
-    let algorithms = vec![Algorithm::Fixedsize, Algorithm::GearCDC, Algorithm::FastCDC];
+    let mut group = c.benchmark_group("rabin-construction");
+    for size in BENCH_SMALL_SIZES {
+        let data = &data_block[..size];
+        group.throughput(Throughput::Bytes(size as u64));
 
-    // Run the chunking algorithms
-    algorithms.iter().for_each(|algo| {
-        run_chunking_algorithm(&mut group, algo, &data_block);
-    });
+        group.bench_function(format!("Built tables/{}", size), |b| {
+            b.iter(|| {
+                let tables = RabinTables::new(BENCH_WIN_SIZE);
+                let chunks: Vec<_> = RabinCDC::with_tables(
+                    black_box(data),
+                    &tables,
+                    BENCH_MIN_CHUNK_SIZE,
+                    BENCH_AVG_CHUNK_SIZE,
+                    BENCH_MAX_CHUNK_SIZE,
+                )
+                .collect();
+                black_box(chunks);
+            })
+        });
+        group.bench_function(format!("Shared tables/{}", size), |b| {
+            b.iter(|| {
+                let chunks: Vec<_> = RabinCDC::with_tables(
+                    black_box(data),
+                    &shared,
+                    BENCH_MIN_CHUNK_SIZE,
+                    BENCH_AVG_CHUNK_SIZE,
+                    BENCH_MAX_CHUNK_SIZE,
+                )
+                .collect();
+                black_box(chunks);
+            })
+        });
+    }
 }
 
-criterion_group!(benches, run_benchmark);
+criterion_group!(
+    benches,
+    run_benchmark,
+    run_jump_benchmark,
+    run_parallel_benchmark,
+    run_rabin_construction_benchmark
+);
 criterion_main!(benches);
@@ -1,4 +1,39 @@
 use std::collections::HashSet;
+use std::path::Path;
+
+use crate::chunking;
+use crate::opts::ChunkingAlgo;
+
+/// Chunk two files with the provided algorithm and calculate their deduplication ratio
+///
+/// # Arguments
+///
+/// * `original` - Path to the original file
+/// * `edited` - Path to the edited file
+/// * `algo` - The chunking algorithm to use
+///
+/// # Returns
+///
+/// * Deduplication ratio as a `f64`
+pub fn chunk_and_calculate_ratio(
+    original: &Path,
+    edited: &Path,
+    algo: &ChunkingAlgo,
+) -> Result<f64, Box<dyn std::error::Error>> {
+    // Read data and split into chunks based on the algorithm specified
+    let hashes_original = chunking::read_and_chunk_data(original, algo)?
+        .iter()
+        .map(|chunk| chunk.hash)
+        .collect::<Vec<_>>();
+
+    // Read data and split into chunks based on the algorithm specified
+    let hashes_edited = chunking::read_and_chunk_data(edited, algo)?
+        .iter()
+        .map(|chunk| chunk.hash)
+        .collect::<Vec<_>>();
+
+    Ok(calculate_deduplication_ratio(&hashes_original, &hashes_edited))
+}
 
 /// Calculate a deduplication ratio between two vectors of hashes
 ///
@@ -46,20 +46,17 @@ pub fn build_distribution(
     // Calculate the total number of chunks
     let total_number_chunks = distribution.values().sum::<u64>();
 
-    // Calculate the percentage of the maximum chunk size
+    // Determine the maximum chunk size based on the chunking algorithm
+    let max_x = algo.chunk_parms()?.max_chunk_size;
+
+    // Calculate the percentage of chunks cut at the maximum chunk size
+    let max_size_chunks = distribution.get(&(max_x as u64)).copied().unwrap_or(0);
     let percentage_max_chunk = if total_number_chunks > 0 {
-        (max_number_chunks as f64 / total_number_chunks as f64) * 100.0
+        (max_size_chunks as f64 / total_number_chunks as f64) * 100.0
     } else {
         0.0
     };
 
-    // Determine the maximum chunk size based on the chunking algorithm
-    let max_x = match algo {
-        ChunkingAlgo::FixedSize(args) => args.chunk_size.get(),
-        ChunkingAlgo::GearCdc(args) => args.max_size.get(),
-        ChunkingAlgo::FastCdc(args) => args.max_size.get(),
-    };
-
     let title = format!(
         "{} distribution - {:.2}% of maximum chunk size",
         algo, percentage_max_chunk
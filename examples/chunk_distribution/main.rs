@@ -1,6 +1,10 @@
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::PathBuf;
 
-use clap::Parser;
+use chunker_benches::CaibxIndex;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 
 mod chunking;
 mod dedup;
@@ -10,6 +14,22 @@ mod opts;
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = opts::Cli::parse();
 
+    // Check the combination of the chunking parameters, which the value parsers only see one by one
+    if let Err(err) = cli.command.algo().chunk_parms() {
+        opts::Cli::command()
+            .error(ErrorKind::ValueValidation, err)
+            .exit();
+    }
+
+    // The default table only reproduces the chunk size distribution of borg, not its boundaries
+    if let opts::ChunkingAlgo::Borg(args) = cli.command.algo() {
+        if args.table_base.is_none() {
+            eprintln!(
+                "Warning: no --table-base given, the chunks won't match the ones of borg"
+            );
+        }
+    }
+
     match &cli.command {
         // Handle distribution command
         opts::Command::Dist(cmd) => {
@@ -63,20 +83,41 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Err(Box::from("Provided path to edited file is a directory"));
             }
 
-            // Read data and split into chunks based on the algorithm specified
-            let hashes_original = chunking::read_and_chunk_data(&cmd.original, &cmd.algo)?
-                .iter()
-                .map(|chunk| chunk.hash)
-                .collect::<Vec<_>>();
+            let ratio = dedup::chunk_and_calculate_ratio(&cmd.original, &cmd.edited, &cmd.algo)?;
+
+            // Jump CDC trades deduplication for throughput, so compare it with plain Gear CDC
+            if let opts::ChunkingAlgo::JumpCdc(args) = &cmd.algo {
+                let baseline = args.baseline();
+                let baseline_ratio =
+                    dedup::chunk_and_calculate_ratio(&cmd.original, &cmd.edited, &baseline)?;
+                println!("{} Deduplication Ratio: X{:.4}", cmd.algo, ratio);
+                println!("{} Deduplication Ratio: X{:.4}", baseline, baseline_ratio);
+                println!("Difference: {:+.4}", ratio - baseline_ratio);
+            } else {
+                println!("Deduplication Ratio: X{:.2}", ratio);
+            }
+
+            Ok(())
+        }
+        // Handle index command
+        opts::Command::Index(cmd) => {
+            // Check if the provided source path exists before proceeding
+            if !cmd.source.exists() {
+                return Err(Box::from("Provided source path doesn't exist"));
+            }
 
             // Read data and split into chunks based on the algorithm specified
-            let hashes_edited = chunking::read_and_chunk_data(&cmd.edited, &cmd.algo)?
-                .iter()
-                .map(|chunk| chunk.hash)
-                .collect::<Vec<_>>();
+            let data_chunks = chunking::read_and_chunk_data(&cmd.source, &cmd.algo)?;
+
+            // Index the chunks by their SHA-512/256 digests
+            let mut index = CaibxIndex::new(cmd.algo.chunk_parms()?);
+            for chunk in &data_chunks {
+                index.push(&chunk.data_chunk);
+            }
 
-            let ratio = dedup::calculate_deduplication_ratio(&hashes_original, &hashes_edited);
-            println!("Deduplication Ratio: X{:.2}", ratio);
+            let file = BufWriter::new(File::create(&cmd.out)?);
+            index.write_to(file)?;
+            println!("Wrote index of {} chunks", index.entries.len());
 
             Ok(())
         }
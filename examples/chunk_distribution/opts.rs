@@ -2,6 +2,14 @@ use std::fmt::{Display, Formatter};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
+use chunker_benches::{
+    AEConfig, BORG_DEFAULT_PARAMS, BorgConfig, BorgParams, BuzhashConfig, CasyncConfig,
+    check_polynomial, ChunkerError, ChunkSizeParms, Chunker, DEFAULT_BUZHASH_SEED, FastCDCConfig,
+    FixedSizeConfig, GearConfig, JumpConfig, MaxpConfig, Normalization, RabinConfig,
+    random_polynomial, RESTIC_AVERAGE_BITS, RESTIC_MAX_SIZE, RESTIC_MIN_SIZE, ResticConfig,
+    TttdConfig,
+};
+
 /// Global program options
 #[derive(clap::Parser)]
 #[command(author, version, about, long_about = None)]
@@ -18,6 +26,19 @@ pub enum Command {
     Dist(DistCmd),
     /// Show a deduplication ratio
     Dedup(DedupCmd),
+    /// Write a casync chunk index
+    Index(IndexCmd),
+}
+
+impl Command {
+    /// Chunking algorithm of the command
+    pub fn algo(&self) -> &ChunkingAlgo {
+        match self {
+            Command::Dist(cmd) => &cmd.algo,
+            Command::Dedup(cmd) => &cmd.algo,
+            Command::Index(cmd) => &cmd.algo,
+        }
+    }
 }
 
 /// Arguments for the `Dist` command
@@ -52,6 +73,22 @@ pub struct DedupCmd {
     pub algo: ChunkingAlgo,
 }
 
+/// Arguments for the `Index` command
+#[derive(clap::Args)]
+pub struct IndexCmd {
+    /// Source dataset to be chunked
+    #[arg(short, long)]
+    pub source: PathBuf,
+
+    /// Path of the `.caibx` index to write
+    #[arg(short, long)]
+    pub out: PathBuf,
+
+    /// Chunking algorithm to use
+    #[command(subcommand)]
+    pub algo: ChunkingAlgo,
+}
+
 /// Chunking algorithms available
 #[derive(clap::Subcommand)]
 pub enum ChunkingAlgo {
@@ -61,6 +98,24 @@ pub enum ChunkingAlgo {
     GearCdc(GearCdcArgs),
     /// Fast Content-Defined Chunking
     FastCdc(FastCdcArgs),
+    /// Buzhash Content-Defined Chunking
+    BuzhashCdc(BuzhashCdcArgs),
+    /// Asymmetric Extremum Content-Defined Chunking
+    AeCdc(AeCdcArgs),
+    /// Two Thresholds Two Divisors Content-Defined Chunking
+    TttdCdc(TttdCdcArgs),
+    /// Local maximum Content-Defined Chunking
+    MaxpCdc(MaxpCdcArgs),
+    /// Jump-ahead Gear Content-Defined Chunking
+    JumpCdc(JumpCdcArgs),
+    /// Rabin Content-Defined Chunking
+    RabinCdc(RabinCdcArgs),
+    /// Rabin Content-Defined Chunking compatible with restic
+    ResticCdc(ResticCdcArgs),
+    /// Buzhash Content-Defined Chunking compatible with borg
+    Borg(BorgArgs),
+    /// Buzhash Content-Defined Chunking compatible with casync and desync
+    CasyncCdc(CasyncCdcArgs),
 }
 
 /// Parameters for fixed size chunking algorithm
@@ -75,13 +130,13 @@ pub struct FixedSizeArgs {
 #[derive(clap::Args)]
 pub struct GearCdcArgs {
     /// Minimum chunk size
-    #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    #[arg(long, value_parser = parse_min_size)]
     pub min_size: NonZeroUsize,
     /// Average chunk size
-    #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    #[arg(long, value_parser = parse_avg_size)]
     pub avg_size: NonZeroUsize,
     /// Maximum chunk size
-    #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    #[arg(long, value_parser = parse_max_size)]
     pub max_size: NonZeroUsize,
 }
 
@@ -89,27 +144,515 @@ pub struct GearCdcArgs {
 #[derive(clap::Args)]
 pub struct FastCdcArgs {
     /// Minimum chunk size
-    #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    #[arg(long, value_parser = parse_min_size)]
     pub min_size: NonZeroUsize,
     /// Average chunk size
-    #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+    /// Normalization level, from 0 (none) to 3
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=3))]
+    pub normalization: u8,
+}
+
+/// Parameters for Buzhash CDC
+#[derive(clap::Args)]
+pub struct BuzhashCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+    /// Size of the rolling hash window
+    #[arg(long, default_value_t = 64)]
+    pub window_size: usize,
+    /// Seed of the Buzhash table
+    #[arg(long, default_value_t = DEFAULT_BUZHASH_SEED)]
+    pub seed: u64,
+}
+
+/// Parameters for Asymmetric Extremum CDC
+#[derive(clap::Args)]
+pub struct AeCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+}
+
+/// Parameters for Two Thresholds Two Divisors CDC
+#[derive(clap::Args)]
+pub struct TttdCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+}
+
+/// Parameters for local maximum CDC
+#[derive(clap::Args)]
+pub struct MaxpCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
     pub avg_size: NonZeroUsize,
     /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+}
+
+/// Parameters for jump-ahead CDC
+#[derive(clap::Args)]
+pub struct JumpCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+    /// Number of bytes skipped by a jump, half the average minus the minimum size by default
     #[arg(long, value_parser = parse_humansize_nonzero_large)]
+    pub jump_length: Option<NonZeroUsize>,
+}
+
+/// Parameters for Rabin CDC
+#[derive(clap::Args)]
+pub struct RabinCdcArgs {
+    /// Minimum chunk size
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Average chunk size
+    #[arg(long, value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, value_parser = parse_max_size)]
     pub max_size: NonZeroUsize,
+    /// Size of the rolling hash window, a power of two from 8 to 64
+    #[arg(long, default_value_t = 64)]
+    pub window_size: usize,
+    /// Polynomial of the fingerprint in hexadecimal
+    #[arg(long, default_value = "3da3358b4dc173", value_parser = parse_polynomial)]
+    pub polynomial: u64,
+    /// Seed of a random irreducible polynomial of degree 53 to use instead of the polynomial
+    #[arg(long, conflicts_with = "polynomial")]
+    pub polynomial_seed: Option<u64>,
+}
+
+/// Parameters for restic compatible CDC
+#[derive(clap::Args)]
+pub struct ResticCdcArgs {
+    /// Polynomial of the restic repository in hexadecimal
+    #[arg(long, default_value = "3da3358b4dc173", value_parser = parse_polynomial)]
+    pub polynomial: u64,
+    /// Number of fingerprint bits that must be zero at a cut point
+    #[arg(long, default_value_t = RESTIC_AVERAGE_BITS)]
+    pub average_bits: u32,
+    /// Minimum chunk size
+    #[arg(long, default_value_t = NonZeroUsize::new(RESTIC_MIN_SIZE).unwrap(),
+          value_parser = parse_min_size)]
+    pub min_size: NonZeroUsize,
+    /// Maximum chunk size
+    #[arg(long, default_value_t = NonZeroUsize::new(RESTIC_MAX_SIZE).unwrap(),
+          value_parser = parse_max_size)]
+    pub max_size: NonZeroUsize,
+}
+
+/// Parameters for borg compatible CDC
+#[derive(clap::Args)]
+pub struct BorgArgs {
+    /// Chunker parameters of borg, as exponents like `19,23,21,4095`
+    #[arg(long, default_value = "19,23,21,4095", value_parser = parse_borg_params)]
+    pub chunker_params: BorgParams,
+    /// Chunk seed of the borg repository key
+    #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+    pub seed: i64,
+    /// File with the 256 comma or whitespace separated values of the buzhash base table, which is
+    /// needed for the boundaries of borg
+    #[arg(long, value_parser = parse_table_base)]
+    pub table_base: Option<Box<[u32; 256]>>,
+}
+
+/// Parameters for casync compatible CDC
+#[derive(clap::Args)]
+pub struct CasyncCdcArgs {
+    /// Minimum chunk size, a quarter of the average size by default
+    #[arg(long, value_parser = parse_min_size)]
+    pub min_size: Option<NonZeroUsize>,
+    /// Average chunk size
+    #[arg(long, default_value_t = NonZeroUsize::new(64 * 1024).unwrap(),
+          value_parser = parse_avg_size)]
+    pub avg_size: NonZeroUsize,
+    /// Maximum chunk size, four times the average size by default
+    #[arg(long, value_parser = parse_max_size)]
+    pub max_size: Option<NonZeroUsize>,
+    /// File with the 256 comma or whitespace separated values of the buzhash table, the table of
+    /// casync by default
+    #[arg(long, value_parser = parse_table_base)]
+    pub table: Option<Box<[u32; 256]>>,
+}
+
+impl FixedSizeArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<FixedSizeConfig, ChunkerError> {
+        FixedSizeConfig::try_new(self.chunk_size.get())
+    }
+}
+
+impl GearCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<GearConfig, ChunkerError> {
+        GearConfig::try_new(ChunkSizeParms {
+            min_chunk_size: self.min_size.get(),
+            avg_chunk_size: self.avg_size.get(),
+            max_chunk_size: self.max_size.get(),
+        })
+    }
+}
+
+impl FastCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<FastCDCConfig, ChunkerError> {
+        let level = match self.normalization {
+            0 => Normalization::Level0,
+            1 => Normalization::Level1,
+            2 => Normalization::Level2,
+            _ => Normalization::Level3,
+        };
+
+        FastCDCConfig::try_new(
+            ChunkSizeParms {
+                min_chunk_size: self.min_size.get(),
+                avg_chunk_size: self.avg_size.get(),
+                max_chunk_size: self.max_size.get(),
+            },
+            level,
+        )
+    }
+}
+
+impl BuzhashCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<BuzhashConfig, ChunkerError> {
+        BuzhashConfig::try_new(
+            self.window_size,
+            self.seed,
+            ChunkSizeParms {
+                min_chunk_size: self.min_size.get(),
+                avg_chunk_size: self.avg_size.get(),
+                max_chunk_size: self.max_size.get(),
+            },
+        )
+    }
+}
+
+impl AeCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<AEConfig, ChunkerError> {
+        AEConfig::try_new(ChunkSizeParms {
+            min_chunk_size: self.min_size.get(),
+            avg_chunk_size: self.avg_size.get(),
+            max_chunk_size: self.max_size.get(),
+        })
+    }
+}
+
+impl TttdCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<TttdConfig, ChunkerError> {
+        TttdConfig::try_new(ChunkSizeParms {
+            min_chunk_size: self.min_size.get(),
+            avg_chunk_size: self.avg_size.get(),
+            max_chunk_size: self.max_size.get(),
+        })
+    }
+}
+
+impl MaxpCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<MaxpConfig, ChunkerError> {
+        MaxpConfig::try_new(ChunkSizeParms {
+            min_chunk_size: self.min_size.get(),
+            avg_chunk_size: self.avg_size.get(),
+            max_chunk_size: self.max_size.get(),
+        })
+    }
+}
+
+impl JumpCdcArgs {
+    /// Number of bytes skipped by a jump
+    pub fn jump_length(&self) -> usize {
+        self.jump_length.map_or_else(
+            || (self.avg_size.get().saturating_sub(self.min_size.get()) / 2).max(1),
+            NonZeroUsize::get,
+        )
+    }
+
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<JumpConfig, ChunkerError> {
+        JumpConfig::try_new(
+            self.jump_length(),
+            ChunkSizeParms {
+                min_chunk_size: self.min_size.get(),
+                avg_chunk_size: self.avg_size.get(),
+                max_chunk_size: self.max_size.get(),
+            },
+        )
+    }
+
+    /// Gear CDC with the same chunk sizes, which Jump CDC is compared with
+    pub fn baseline(&self) -> ChunkingAlgo {
+        ChunkingAlgo::GearCdc(GearCdcArgs {
+            min_size: self.min_size,
+            avg_size: self.avg_size,
+            max_size: self.max_size,
+        })
+    }
+}
+
+impl RabinCdcArgs {
+    /// Polynomial of the fingerprint, generated from the seed if one is given
+    pub fn polynomial(&self) -> u64 {
+        self.polynomial_seed.map_or(self.polynomial, random_polynomial)
+    }
+
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<RabinConfig, ChunkerError> {
+        RabinConfig::try_with_polynomial(
+            self.polynomial(),
+            self.window_size,
+            ChunkSizeParms {
+                min_chunk_size: self.min_size.get(),
+                avg_chunk_size: self.avg_size.get(),
+                max_chunk_size: self.max_size.get(),
+            },
+        )
+    }
+}
+
+impl ResticCdcArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<ResticConfig, ChunkerError> {
+        ResticConfig::try_with_boundaries(
+            self.polynomial,
+            self.average_bits,
+            self.min_size.get(),
+            self.max_size.get(),
+        )
+    }
+}
+
+impl BorgArgs {
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<BorgConfig, ChunkerError> {
+        // Borg only uses the low 32 bits of the seed, which may be negative in the key.
+        let seed = self.seed as u32;
+        let config = BorgConfig::try_new(seed, self.chunker_params)?;
+        Ok(match self.table_base.as_deref() {
+            Some(&table_base) => BorgConfig {
+                table_base,
+                ..config
+            },
+            None => config,
+        })
+    }
+}
+
+impl CasyncCdcArgs {
+    /// Chunk sizes, with the minimum and maximum sizes derived from the average like in casync
+    pub fn chunk_parms(&self) -> ChunkSizeParms {
+        let avg_size = self.avg_size.get();
+        ChunkSizeParms {
+            min_chunk_size: self.min_size.map_or(avg_size / 4, NonZeroUsize::get),
+            avg_chunk_size: avg_size,
+            max_chunk_size: self.max_size.map_or(avg_size * 4, NonZeroUsize::get),
+        }
+    }
+
+    /// Build the chunker configured by the arguments
+    pub fn chunker(&self) -> Result<CasyncConfig, ChunkerError> {
+        let chunk_parms = self.chunk_parms();
+        let config = CasyncConfig::try_new(chunk_parms)?;
+        Ok(match self.table.as_deref() {
+            Some(&table) => CasyncConfig { table, ..config },
+            None => config,
+        })
+    }
+}
+
+impl ChunkingAlgo {
+    /// Chunk size parameters of the selected algorithm
+    ///
+    /// Building the chunker checks all of its parameters, so this also reports combinations of
+    /// arguments that are invalid, like a minimum size above the average size.
+    pub fn chunk_parms(&self) -> Result<ChunkSizeParms, ChunkerError> {
+        Ok(match self {
+            ChunkingAlgo::FixedSize(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::GearCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::FastCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::BuzhashCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::AeCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::TttdCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::MaxpCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::JumpCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::RabinCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::ResticCdc(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::Borg(args) => args.chunker()?.chunk_parms(),
+            ChunkingAlgo::CasyncCdc(args) => args.chunker()?.chunk_parms(),
+        })
+    }
 }
 
 impl Display for ChunkingAlgo {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            ChunkingAlgo::FixedSize(_) => "Fixed Size Chunking",
-            ChunkingAlgo::GearCdc(_) => "Gear CDC Chunking",
-            ChunkingAlgo::FastCdc(_) => "Fast CDC Chunking",
-        };
-        write!(f, "{}", str)
+        match self {
+            ChunkingAlgo::FixedSize(_) => write!(f, "Fixed Size Chunking"),
+            ChunkingAlgo::GearCdc(_) => write!(f, "Gear CDC Chunking"),
+            ChunkingAlgo::FastCdc(args) => {
+                write!(f, "Fast CDC Chunking NC{}", args.normalization)
+            }
+            ChunkingAlgo::BuzhashCdc(_) => write!(f, "Buzhash CDC Chunking"),
+            ChunkingAlgo::AeCdc(_) => write!(f, "AE CDC Chunking"),
+            ChunkingAlgo::TttdCdc(_) => write!(f, "TTTD CDC Chunking"),
+            ChunkingAlgo::MaxpCdc(_) => write!(f, "MAXP CDC Chunking"),
+            ChunkingAlgo::JumpCdc(args) => write!(f, "Jump CDC Chunking J{}", args.jump_length()),
+            ChunkingAlgo::RabinCdc(args) => {
+                write!(f, "Rabin CDC Chunking P{:x}", args.polynomial())
+            }
+            ChunkingAlgo::ResticCdc(args) => {
+                write!(f, "Restic CDC Chunking P{:x}", args.polynomial)
+            }
+            ChunkingAlgo::Borg(args) => {
+                let params = args.chunker_params;
+                write!(
+                    f,
+                    "Borg CDC Chunking {},{},{},{}",
+                    params.min_exp, params.max_exp, params.mask_bits, params.window_size
+                )
+            }
+            ChunkingAlgo::CasyncCdc(args) => {
+                let parms = args.chunk_parms();
+                write!(
+                    f,
+                    "Casync CDC Chunking {}:{}:{}",
+                    parms.min_chunk_size, parms.avg_chunk_size, parms.max_chunk_size
+                )
+            }
+        }
     }
 }
 
+/// Parse borg chunker parameters like `19,23,21,4095`, optionally prefixed by `buzhash,`
+pub fn parse_borg_params(source: &str) -> Result<BorgParams, String> {
+    let source = source.strip_prefix("buzhash,").unwrap_or(source);
+    if source == "default" {
+        return Ok(BORG_DEFAULT_PARAMS);
+    }
+
+    let values = source
+        .split(',')
+        .map(|value| value.trim().parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("{}, expected four comma separated numbers", err))?;
+
+    let params = match values[..] {
+        [min_exp, max_exp, mask_bits, window_size] => BorgParams {
+            min_exp,
+            max_exp,
+            mask_bits,
+            window_size: window_size as usize,
+        },
+        _ => {
+            return Err(format!(
+                "expected four comma separated numbers, got {}",
+                values.len()
+            ))
+        }
+    };
+
+    // The seed doesn't affect the validity of the parameters
+    BorgConfig::try_new(0, params).map_err(|err| err.to_string())?;
+    Ok(params)
+}
+
+/// Parse a file with the 256 values of a buzhash base table
+///
+/// The values are separated by commas or whitespace and written in decimal or in hexadecimal with
+/// a `0x` prefix, so the table can be copied from a C array initializer.
+pub fn parse_table_base(path: &str) -> Result<Box<[u32; 256]>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+
+    let values = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| match value.strip_prefix("0x") {
+            Some(digits) => u32::from_str_radix(digits, 16),
+            None => value.parse(),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("{}, expected 32-bit table values", err))?;
+
+    values
+        .into_boxed_slice()
+        .try_into()
+        .map_err(|values: Box<[_]>| format!("expected 256 table values, got {}", values.len()))
+}
+
+/// Parse an irreducible hexadecimal polynomial over GF(2) of degree 8 to 56, with an optional `0x`
+/// prefix
+pub fn parse_polynomial(source: &str) -> Result<u64, String> {
+    let digits = source
+        .strip_prefix("0x")
+        .or_else(|| source.strip_prefix("0X"))
+        .unwrap_or(source);
+
+    let polynomial = u64::from_str_radix(digits, 16)
+        .map_err(|err| format!("{}, expected hexadecimal polynomial", err))?;
+
+    check_polynomial(polynomial).map_err(|err| err.to_string())
+}
+
+/// Parse a minimum chunk size with optional unit suffix, checking its range
+pub fn parse_min_size(source: &str) -> Result<NonZeroUsize, String> {
+    let size = parse_humansize_nonzero_large(source)?;
+    ChunkSizeParms::check_min_chunk_size(size.get()).map_err(|err| err.to_string())?;
+    Ok(size)
+}
+
+/// Parse an average chunk size with optional unit suffix, checking its range
+pub fn parse_avg_size(source: &str) -> Result<NonZeroUsize, String> {
+    let size = parse_humansize_nonzero_large(source)?;
+    ChunkSizeParms::check_avg_chunk_size(size.get()).map_err(|err| err.to_string())?;
+    Ok(size)
+}
+
+/// Parse a maximum chunk size with optional unit suffix, checking its range
+pub fn parse_max_size(source: &str) -> Result<NonZeroUsize, String> {
+    let size = parse_humansize_nonzero_large(source)?;
+    ChunkSizeParms::check_max_chunk_size(size.get()).map_err(|err| err.to_string())?;
+    Ok(size)
+}
+
 /// Parse a string containing large positive size value with optional unit prefix
 ///
 /// Parse a string containing a size which value in bytes does not exceed [`u64::MAX`] into
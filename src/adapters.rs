@@ -0,0 +1,182 @@
+//! Adapters exposing the `fastcdc` crate implementations through the [`Chunker`] interface.
+//!
+//! [`Chunker`]: super::Chunker
+
+/// Converts a chunk produced by `fastcdc::ronomon` into the crate chunk.
+fn from_ronomon(chunk: fastcdc::ronomon::Chunk) -> super::Chunk {
+    super::Chunk {
+        offset: chunk.offset,
+        length: chunk.length,
+    }
+}
+
+/// Converts a chunk produced by `fastcdc::v2020` into the crate chunk.
+fn from_v2020(chunk: fastcdc::v2020::Chunk) -> super::Chunk {
+    super::Chunk {
+        offset: chunk.offset,
+        length: chunk.length,
+    }
+}
+
+/// Configuration of the Gear based FastCDC implementation by Joran Dirk Greef
+/// (`fastcdc::ronomon`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RonomonConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl RonomonConfig {
+    /// Constructs a new `RonomonConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `RonomonConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        super::ChunkSizeParms::new(
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for RonomonConfig {
+    type Iter<'a> =
+        std::iter::Map<fastcdc::ronomon::FastCDC<'a>, fn(fastcdc::ronomon::Chunk) -> super::Chunk>;
+
+    fn name(&self) -> &'static str {
+        "Gear Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        fastcdc::ronomon::FastCDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+        .map(from_ronomon as fn(_) -> _)
+    }
+}
+
+/// Configuration of the canonical FastCDC 2020 implementation (`fastcdc::v2020`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct V2020Config {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl V2020Config {
+    /// Constructs a new `V2020Config`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `V2020Config`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        super::ChunkSizeParms::new(
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for V2020Config {
+    type Iter<'a> =
+        std::iter::Map<fastcdc::v2020::FastCDC<'a>, fn(fastcdc::v2020::Chunk) -> super::Chunk>;
+
+    fn name(&self) -> &'static str {
+        "Fast Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        // The crate starts the search one byte before an odd minimum, which would allow a chunk
+        // shorter than the minimum.
+        let min_size = self.chunk_parms.min_chunk_size.next_multiple_of(2);
+        fastcdc::v2020::FastCDC::new(
+            source,
+            min_size.min(self.chunk_parms.max_chunk_size) as u32,
+            self.chunk_parms.avg_chunk_size as u32,
+            self.chunk_parms.max_chunk_size as u32,
+        )
+        .map(from_v2020 as fn(_) -> _)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkSizeParms, Chunker};
+
+    /// Chunk size parameters shared by the adapter tests.
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 1024,
+        avg_chunk_size: 4096,
+        max_chunk_size: 16384,
+    };
+
+    /// Tests that the ronomon adapter yields the same chunks as the wrapped implementation.
+    #[test]
+    fn test_ronomon_adapter() {
+        let data = crate::generate_data_block(256 * 1024, Some(7));
+        let expected: Vec<_> = fastcdc::ronomon::FastCDC::new(&data, 1024, 4096, 16384)
+            .map(|chunk| (chunk.offset, chunk.length))
+            .collect();
+
+        let chunks: Vec<_> = RonomonConfig::new(PARMS)
+            .chunks(&data)
+            .map(|chunk| (chunk.offset, chunk.length))
+            .collect();
+
+        assert_eq!(chunks, expected);
+    }
+
+    /// Tests that the v2020 adapter yields the same chunks as the wrapped implementation.
+    #[test]
+    fn test_v2020_adapter() {
+        let data = crate::generate_data_block(256 * 1024, Some(7));
+        let expected: Vec<_> = fastcdc::v2020::FastCDC::new(&data, 1024, 4096, 16384)
+            .map(|chunk| (chunk.offset, chunk.length))
+            .collect();
+
+        let chunks: Vec<_> = V2020Config::new(PARMS)
+            .chunks(&data)
+            .map(|chunk| (chunk.offset, chunk.length))
+            .collect();
+
+        assert_eq!(chunks, expected);
+    }
+}
@@ -0,0 +1,236 @@
+/// Asymmetric Extremum chunker for hashless content defined data segmentation.
+///
+/// The chunker tracks the maximum byte value seen past the minimum chunk size and cuts once a
+/// window of bytes following the maximum contains no greater value. The window is derived from
+/// the difference between the average and the minimum chunk size.
+pub struct AECDC<'a> {
+    /// Number of bytes following the maximum that must not exceed it.
+    window: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> AECDC<'a> {
+    /// Constructs a new `AECDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `AECDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::try_new(source, min_size, avg_size, max_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `AECDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        Ok(Self {
+            window: window_size(min_size, avg_size),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        if limit <= min_size {
+            return limit;
+        }
+
+        let data = &data[..limit];
+        let mut max_pos = min_size;
+        let mut max_value = data[min_size];
+
+        for (pos, &byte) in data.iter().enumerate().skip(min_size + 1) {
+            if byte > max_value {
+                max_value = byte;
+                max_pos = pos;
+            } else if pos == max_pos + self.window {
+                return pos + 1;
+            }
+        }
+
+        limit
+    }
+}
+
+/// Computes the extremum window giving the requested average chunk size.
+///
+/// With distinct random values the expected distance from the minimum chunk size to the cut point
+/// is `window * (e - 1)`. Bytes only take 256 values though, so with longer windows the maximum is
+/// almost always the first `0xFF` byte, found 256 bytes past the minimum on average, and the cut
+/// point follows a window later.
+fn window_size(min_size: usize, avg_size: usize) -> usize {
+    let target = avg_size.saturating_sub(min_size);
+    let ratio = (target as f64 / (std::f64::consts::E - 1.0)).round() as usize;
+    target.saturating_sub(256).max(ratio).max(1)
+}
+
+/// Configuration of the Asymmetric Extremum chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AEConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl AEConfig {
+    /// Constructs a new `AEConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `AEConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        AECDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for AEConfig {
+    type Iter<'a> = AECDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Asymmetric Extremum Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        AECDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for AECDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the extreme byte values.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that every content defined cut follows a window of bytes not exceeding the maximum.
+    #[test]
+    fn test_cut_follows_extremum() {
+        let data = crate::generate_data_block(512 * 1024, Some(41));
+        let (min, avg, max) = (256, 1024, 4096);
+        let window = window_size(min, avg);
+
+        let chunks: Vec<_> = AECDC::new(&data, min, avg, max).collect();
+        for chunk in &chunks[..chunks.len() - 1] {
+            if chunk.length == max {
+                continue;
+            }
+            let bytes = &data[chunk.offset..chunk.offset + chunk.length];
+            let extremum = chunk.length - 1 - window;
+            assert!(extremum >= min, "Chunk at {}", chunk.offset);
+            assert!(bytes[min..extremum].iter().all(|&b| b < bytes[extremum]));
+            assert!(bytes[extremum + 1..].iter().all(|&b| b <= bytes[extremum]));
+        }
+    }
+
+    /// Tests that the average chunk size of random data is close to the requested one.
+    #[test]
+    fn test_random_average() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(42));
+        let chunks: Vec<_> = AECDC::new(&data, 2048, 8192, 65536).collect();
+
+        let mut offset = 0;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 65536);
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+
+        let average = data.len() / chunks.len();
+        assert!((7168..=9216).contains(&average), "Average {}", average);
+    }
+
+    /// Tests that constant data is cut right after the window following the minimum chunk size.
+    #[test]
+    fn test_constant_data() {
+        let data = vec![7u8; 100_000];
+        let chunks: Vec<_> = AECDC::new(&data, 1024, 4096, 16384).collect();
+
+        let length = 1024 + window_size(1024, 4096) + 1;
+        assert_eq!(chunks.len(), data.len().div_ceil(length));
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.length == length));
+    }
+}
@@ -0,0 +1,180 @@
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::io::AsyncRead;
+use futures::Stream;
+
+use super::stream::{ChunkBuffer, ChunkData, DEFAULT_BUFFER_SIZE};
+use super::Chunker;
+
+/// Chunker reading its source from a [`futures::io::AsyncRead`] instance.
+///
+/// Yields the same boundaries as chunking the whole source in memory, along with owned copies of
+/// the chunk bytes.
+pub struct AsyncStreamChunker<R, C> {
+    /// Reader providing the stream.
+    reader: R,
+    /// Buffer holding the not yet emitted part of the stream.
+    buffer: ChunkBuffer<C>,
+}
+
+impl<R: AsyncRead + Unpin, C: Chunker + Unpin> AsyncStreamChunker<R, C> {
+    /// Constructs a new `AsyncStreamChunker` with a buffer of [`DEFAULT_BUFFER_SIZE`] bytes.
+    ///
+    /// # Arguments
+    /// * `reader` - Reader providing the stream to be chunked.
+    /// * `chunker` - Chunking algorithm configuration.
+    ///
+    /// # Returns
+    ///
+    /// A new `AsyncStreamChunker` instance.
+    pub fn new(reader: R, chunker: C) -> Self {
+        Self::with_buffer_size(reader, chunker, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Constructs a new `AsyncStreamChunker` with a custom buffer size.
+    ///
+    /// # Arguments
+    /// * `reader` - Reader providing the stream to be chunked.
+    /// * `chunker` - Chunking algorithm configuration.
+    /// * `buffer_size` - Size of the read buffer. It is raised above the maximum chunk size plus
+    ///   the lookahead of the chunker if smaller.
+    ///
+    /// # Returns
+    ///
+    /// A new `AsyncStreamChunker` instance.
+    pub fn with_buffer_size(reader: R, chunker: C, buffer_size: usize) -> Self {
+        Self {
+            reader,
+            buffer: ChunkBuffer::new(chunker, buffer_size),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin, C: Chunker + Unpin> Stream for AsyncStreamChunker<R, C> {
+    type Item = std::io::Result<ChunkData>;
+
+    /// Computes the next chunk of the stream, reading more data if required.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ChunkData` if available, `None` if an end of stream is reached, or an error if
+    /// reading the stream failed.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        while this.buffer.needs_data() {
+            match Pin::new(&mut this.reader).poll_read(cx, this.buffer.spare()) {
+                Poll::Ready(Ok(len)) => this.buffer.commit(len),
+                Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => continue,
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(this.buffer.pop().map(|(chunk, data)| {
+            Ok(ChunkData {
+                offset: chunk.offset,
+                data: data.to_vec(),
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use futures::{StreamExt, TryStreamExt};
+
+    use super::*;
+    use crate::{
+        ChunkSizeParms, FastCDCConfig, FixedSizeConfig, GearConfig, Normalization, RabinConfig,
+    };
+
+    /// Chunk size parameters used by the stream tests.
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 1024,
+        avg_chunk_size: 4096,
+        max_chunk_size: 16384,
+    };
+
+    /// Reader returning at most `step` bytes per call, which is pending every other call.
+    struct PendingReader<'a> {
+        data: &'a [u8],
+        step: usize,
+        ready: bool,
+    }
+
+    impl AsyncRead for PendingReader<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let len = self.step.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Poll::Ready(Ok(len))
+        }
+    }
+
+    /// Checks that the async stream yields the same chunks as chunking the data in memory.
+    fn assert_stream_matches<C: Chunker + Clone + Unpin>(chunker: C, data: &[u8]) {
+        let expected: Vec<_> = chunker
+            .chunks(data)
+            .map(|chunk| ChunkData {
+                offset: chunk.offset as u64,
+                data: data[chunk.offset..chunk.offset + chunk.length].to_vec(),
+            })
+            .collect();
+
+        for buffer_size in [0, 40000, 1024 * 1024] {
+            let stream = AsyncStreamChunker::with_buffer_size(
+                Cursor::new(data),
+                chunker.clone(),
+                buffer_size,
+            );
+            let chunks: Vec<_> = block_on(stream.try_collect()).unwrap();
+            assert_eq!(chunks, expected, "Buffer size {}", buffer_size);
+
+            let reader = PendingReader {
+                data,
+                step: 1000,
+                ready: false,
+            };
+            let stream = AsyncStreamChunker::with_buffer_size(reader, chunker.clone(), buffer_size);
+            let chunks: Vec<_> = block_on(stream.try_collect()).unwrap();
+            assert_eq!(
+                chunks, expected,
+                "Pending reader, buffer size {}",
+                buffer_size
+            );
+        }
+    }
+
+    /// Tests that the boundaries match the slice iterators for the crate chunkers.
+    #[test]
+    fn test_matches_in_memory() {
+        let data = crate::generate_data_block(512 * 1024 + 17, Some(21));
+
+        assert_stream_matches(FixedSizeConfig::new(3000), &data);
+        assert_stream_matches(GearConfig::new(PARMS), &data);
+        assert_stream_matches(FastCDCConfig::new(PARMS, Normalization::Level1), &data);
+        assert_stream_matches(RabinConfig::new(32, PARMS), &data);
+    }
+
+    /// Tests streaming of an empty source.
+    #[test]
+    fn test_empty_stream() {
+        let stream = AsyncStreamChunker::new(Cursor::new(Vec::new()), GearConfig::new(PARMS));
+        assert_eq!(block_on(stream.count()), 0);
+    }
+}
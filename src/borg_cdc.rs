@@ -0,0 +1,427 @@
+use super::buzhash_cdc::{buzhash_table, DEFAULT_BUZHASH_SEED};
+
+/// Minimum and maximum window sizes for chunking.
+const MIN_WIN_SIZE: usize = 7;
+const MAX_WIN_SIZE: usize = 16_383;
+
+/// Chunker parameters of borg, given as exponents of two like `chunker_params`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BorgParams {
+    /// Exponent of the minimum chunk size.
+    pub min_exp: u32,
+    /// Exponent of the maximum chunk size.
+    pub max_exp: u32,
+    /// Number of hash bits that must be zero at a cut point.
+    pub mask_bits: u32,
+    /// Size of the sliding window, which must be odd.
+    pub window_size: usize,
+}
+
+/// Default chunker parameters of borg, `19,23,21,4095`.
+pub const BORG_DEFAULT_PARAMS: BorgParams = BorgParams {
+    min_exp: 19,
+    max_exp: 23,
+    mask_bits: 21,
+    window_size: 4095,
+};
+
+impl BorgParams {
+    /// Returns the chunk sizes given by the exponents.
+    ///
+    /// The average is the minimum chunk size plus the expected distance to a cut point, capped
+    /// at the maximum chunk size. Exponents too large for a `usize` give a size of zero.
+    pub fn chunk_parms(&self) -> super::ChunkSizeParms {
+        let (min_size, avg_size, max_size) = self.chunk_sizes();
+
+        super::ChunkSizeParms {
+            min_chunk_size: min_size,
+            avg_chunk_size: avg_size,
+            max_chunk_size: max_size,
+        }
+    }
+
+    /// Returns the chunk sizes given by the exponents, checking them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated by the minimum, average or
+    /// maximum chunk size.
+    pub fn try_chunk_parms(&self) -> Result<super::ChunkSizeParms, super::ChunkerError> {
+        let (min_size, avg_size, max_size) = self.chunk_sizes();
+        super::ChunkSizeParms::new(min_size, avg_size, max_size)
+    }
+
+    /// Computes the minimum, average and maximum chunk sizes.
+    fn chunk_sizes(&self) -> (usize, usize, usize) {
+        let size = |exp: u32| 1usize.checked_shl(exp).unwrap_or(0);
+        let (min_size, max_size) = (size(self.min_exp), size(self.max_exp));
+
+        (
+            min_size,
+            min_size.saturating_add(size(self.mask_bits)).min(max_size),
+            max_size,
+        )
+    }
+}
+
+/// Buzhash chunker following the chunker of borg.
+///
+/// Borg XORs the values of a fixed base table with a seed taken from the repository key, and
+/// hashes windows starting at the minimum chunk size. Unlike most chunkers, a chunk ends at the
+/// start of the first window whose hash has the low `mask_bits` bits zero, so the window belongs
+/// to the next chunk. A window has to end before the maximum chunk size or the end of the data,
+/// otherwise the chunk spans up to that limit.
+///
+/// The base table of borg isn't included, so the boundaries can only follow borg when it is
+/// given, see [`BorgCDC::with_table_base`], and they haven't been checked against the output of
+/// borg. Any other random base table yields the same chunk size distribution, so the default
+/// table of [`BuzhashCDC`](super::BuzhashCDC) is used otherwise.
+pub struct BorgCDC<'a> {
+    /// Base table values XORed with the seed.
+    table: [u32; 256],
+    /// Table values rotated by the window size, removing the byte leaving the window.
+    out_table: [u32; 256],
+    /// Size of the sliding window.
+    win_size: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Mask used to determine chunk cuts.
+    cut_mask: u32,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> BorgCDC<'a> {
+    /// Constructs a new `BorgCDC` using the table generated from [`DEFAULT_BUZHASH_SEED`] as base.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the window size is even or out of range, or if any of the size constraints are
+    /// violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `BorgCDC` instance.
+    pub fn new(source: &'a [u8], seed: u32, params: BorgParams) -> Self {
+        Self::with_table_base(source, seed, params, buzhash_table(DEFAULT_BUZHASH_SEED))
+    }
+
+    /// Constructs a new `BorgCDC` using the table generated from [`DEFAULT_BUZHASH_SEED`] as base,
+    /// checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window size is even or out of range, or if any of the size
+    /// constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        seed: u32,
+        params: BorgParams,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_table_base(source, seed, params, buzhash_table(DEFAULT_BUZHASH_SEED))
+    }
+
+    /// Constructs a new `BorgCDC` using a custom base table.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    /// * `table_base` - Base table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the window size is even or out of range, or if any of the size constraints are
+    /// violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `BorgCDC` instance.
+    pub fn with_table_base(
+        source: &'a [u8],
+        seed: u32,
+        params: BorgParams,
+        table_base: [u32; 256],
+    ) -> Self {
+        Self::try_with_table_base(source, seed, params, table_base)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `BorgCDC` using a custom base table, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    /// * `table_base` - Base table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window size is even or out of range, or if any of the size
+    /// constraints are violated.
+    pub fn try_with_table_base(
+        source: &'a [u8],
+        seed: u32,
+        params: BorgParams,
+        table_base: [u32; 256],
+    ) -> Result<Self, super::ChunkerError> {
+        if !(MIN_WIN_SIZE..=MAX_WIN_SIZE).contains(&params.window_size) {
+            return Err(super::ChunkerError::WindowSize(params.window_size));
+        }
+        if params.window_size.is_multiple_of(2) {
+            return Err(super::ChunkerError::EvenWindowSize(params.window_size));
+        }
+        let chunk_parms = params.try_chunk_parms()?;
+        if !(params.min_exp..=params.max_exp).contains(&params.mask_bits) {
+            return Err(super::ChunkerError::MaskBits(params.mask_bits));
+        }
+
+        let table = table_base.map(|value| value ^ seed);
+        let rotation = (params.window_size % 32) as u32;
+
+        Ok(Self {
+            table,
+            out_table: table.map(|value| value.rotate_left(rotation)),
+            win_size: params.window_size,
+            cur_pos: 0,
+            cut_mask: (1u32 << params.mask_bits) - 1,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// Borg only starts a chunk search once the minimum chunk size and a window past it are
+    /// available, so shorter remainders form the last chunk unless they exceed the maximum size.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size + self.win_size {
+            return remaining.min(self.chunk_parms.max_chunk_size);
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        if min_size + self.win_size >= limit {
+            return limit;
+        }
+        let data = &data[..limit];
+
+        let mut start = min_size;
+        let mut hash = data[start..start + self.win_size]
+            .iter()
+            .fold(0u32, |hash, &byte| {
+                hash.rotate_left(1) ^ self.table[byte as usize]
+            });
+
+        loop {
+            if hash & self.cut_mask == 0 {
+                return start;
+            }
+            if start + self.win_size + 1 >= limit {
+                return limit;
+            }
+
+            hash = hash.rotate_left(1)
+                ^ self.out_table[data[start] as usize]
+                ^ self.table[data[start + self.win_size] as usize];
+            start += 1;
+        }
+    }
+}
+
+/// Configuration of the borg chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BorgConfig {
+    /// Seed of the repository, XORed into the table values.
+    pub seed: u32,
+    /// Chunker parameters as exponents of two.
+    pub params: BorgParams,
+    /// Base table mapping each byte value to a pseudo-random 32-bit number.
+    pub table_base: [u32; 256],
+}
+
+impl BorgConfig {
+    /// Constructs a new `BorgConfig` using the table generated from [`DEFAULT_BUZHASH_SEED`] as
+    /// base.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    pub fn new(seed: u32, params: BorgParams) -> Self {
+        Self::with_table_base(seed, params, buzhash_table(DEFAULT_BUZHASH_SEED))
+    }
+
+    /// Constructs a new `BorgConfig` using the table generated from [`DEFAULT_BUZHASH_SEED`] as
+    /// base, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window size is even or out of range, or if any of the size
+    /// constraints are violated.
+    pub fn try_new(seed: u32, params: BorgParams) -> Result<Self, super::ChunkerError> {
+        BorgCDC::try_new(&[], seed, params)?;
+        Ok(Self::new(seed, params))
+    }
+
+    /// Constructs a new `BorgConfig` using a custom base table.
+    ///
+    /// # Arguments
+    /// * `seed` - Seed of the repository, XORed into the table values.
+    /// * `params` - Chunker parameters as exponents of two.
+    /// * `table_base` - Base table mapping each byte value to a pseudo-random 32-bit number.
+    pub fn with_table_base(seed: u32, params: BorgParams, table_base: [u32; 256]) -> Self {
+        Self {
+            seed,
+            params,
+            table_base,
+        }
+    }
+}
+
+impl super::Chunker for BorgConfig {
+    type Iter<'a> = BorgCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Borg Buzhash Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.params.chunk_parms()
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        BorgCDC::with_table_base(source, self.seed, self.params, self.table_base)
+    }
+}
+
+impl Iterator for BorgCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Buzhash rolling hash.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small chunker parameters, `10,16,12,63`.
+    const SMALL_PARAMS: BorgParams = BorgParams {
+        min_exp: 10,
+        max_exp: 16,
+        mask_bits: 12,
+        window_size: 63,
+    };
+
+    /// Computes the hash of a window from scratch, like borg does at the start of a search.
+    fn window_hash(window: &[u8], table: &[u32; 256]) -> u32 {
+        let last = window.len() - 1;
+        window.iter().enumerate().fold(0, |hash, (i, &byte)| {
+            hash ^ table[byte as usize].rotate_left(((last - i) % 32) as u32)
+        })
+    }
+
+    /// Tests that every content defined chunk ends at the start of a matching window.
+    #[test]
+    fn test_cut_at_window_start() {
+        let data = crate::generate_data_block(512 * 1024, Some(173));
+        let chunker = BorgCDC::new(&data, 42, SMALL_PARAMS);
+        let table = chunker.table;
+
+        let chunks: Vec<_> = chunker.collect();
+        for chunk in &chunks[..chunks.len() - 1] {
+            let end = chunk.offset + chunk.length;
+            if chunk.length == 1 << SMALL_PARAMS.max_exp {
+                continue;
+            }
+            let hash = window_hash(&data[end..end + SMALL_PARAMS.window_size], &table);
+            assert_eq!(hash & 0xfff, 0, "Chunk at {}", chunk.offset);
+        }
+    }
+
+    /// Tests that the seed changes the boundaries.
+    #[test]
+    fn test_seed() {
+        let data = crate::generate_data_block(256 * 1024, Some(174));
+        let chunks = |seed| BorgCDC::new(&data, seed, SMALL_PARAMS).collect::<Vec<_>>();
+        assert_eq!(chunks(7), chunks(7));
+        assert_ne!(chunks(7), chunks(8));
+    }
+
+    /// Tests that the chunk sizes given by the exponents are checked together.
+    #[test]
+    fn test_chunk_sizes() {
+        assert_eq!(
+            BORG_DEFAULT_PARAMS.try_chunk_parms().unwrap(),
+            BORG_DEFAULT_PARAMS.chunk_parms()
+        );
+
+        let unordered = BorgParams {
+            min_exp: 16,
+            max_exp: 12,
+            mask_bits: 14,
+            window_size: 63,
+        };
+        assert!(matches!(
+            BorgCDC::try_new(&[], 0, unordered),
+            Err(crate::ChunkerError::UnorderedChunkSizes(_))
+        ));
+
+        // 64 bytes plus 2^6 is below the smallest valid average chunk size.
+        let small_average = BorgParams {
+            min_exp: 6,
+            max_exp: 16,
+            mask_bits: 6,
+            window_size: 63,
+        };
+        assert!(matches!(
+            BorgConfig::try_new(0, small_average),
+            Err(crate::ChunkerError::AvgChunkSize(128))
+        ));
+    }
+
+    /// Tests borg parameters with an even window size.
+    #[test]
+    #[should_panic(expected = "Window size must be odd")]
+    fn test_even_window_size() {
+        let params = BorgParams {
+            window_size: 4096,
+            ..BORG_DEFAULT_PARAMS
+        };
+        let _chunker = BorgCDC::new(&[], 0, params);
+    }
+}
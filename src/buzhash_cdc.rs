@@ -0,0 +1,422 @@
+/// Minimum and maximum window sizes for chunking.
+const MIN_WIN_SIZE: usize = 8;
+const MAX_WIN_SIZE: usize = 16_384;
+
+/// Seed of the default Buzhash table.
+pub const DEFAULT_BUZHASH_SEED: u64 = 0x6275_7a68_6173_6821;
+
+/// Generates a Buzhash table from the given seed.
+///
+/// The table is filled by the SplitMix64 generator, so the same seed always yields the same
+/// table regardless of the platform or the `rand` version.
+///
+/// # Arguments
+///
+/// * `seed` - The seed value to use for generating the table.
+///
+/// # Returns
+///
+/// A table mapping each byte value to a pseudo-random 32-bit number.
+pub fn buzhash_table(seed: u64) -> [u32; 256] {
+    let mut state = seed;
+    let mut table = [0u32; 256];
+    for value in table.iter_mut() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        *value = ((z ^ (z >> 31)) >> 32) as u32;
+    }
+    table
+}
+
+/// Buzhash chunker for data segmentation based on the cyclic polynomial rolling hash.
+///
+/// The hash of the sliding window is the XOR of the table values of its bytes, each rotated by
+/// its distance from the end of the window. A chunk ends at the first position past the minimum
+/// chunk size where the low bits of the hash selected by the cut mask are zero, or at the
+/// maximum chunk size if no such position exists.
+pub struct BuzhashCDC<'a> {
+    /// Table mapping each byte value to a pseudo-random number.
+    table: [u32; 256],
+    /// Table values rotated by the window size, removing the byte leaving the window.
+    out_table: [u32; 256],
+    /// Size of the sliding window.
+    win_size: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Mask used to determine chunk cuts.
+    cut_mask: u32,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> BuzhashCDC<'a> {
+    /// Constructs a new `BuzhashCDC` using the table generated from [`DEFAULT_BUZHASH_SEED`].
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `BuzhashCDC` instance.
+    pub fn new(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::with_seed(
+            source,
+            win_size,
+            min_size,
+            avg_size,
+            max_size,
+            DEFAULT_BUZHASH_SEED,
+        )
+    }
+
+    /// Constructs a new `BuzhashCDC` using the table generated from [`DEFAULT_BUZHASH_SEED`],
+    /// checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_table(
+            source,
+            win_size,
+            min_size,
+            avg_size,
+            max_size,
+            buzhash_table(DEFAULT_BUZHASH_SEED),
+        )
+    }
+
+    /// Constructs a new `BuzhashCDC` using the table generated from the given seed.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `seed` - Seed of the Buzhash table.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `BuzhashCDC` instance.
+    pub fn with_seed(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        seed: u64,
+    ) -> Self {
+        Self::with_table(
+            source,
+            win_size,
+            min_size,
+            avg_size,
+            max_size,
+            buzhash_table(seed),
+        )
+    }
+
+    /// Constructs a new `BuzhashCDC` using a custom table.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `table` - Table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `BuzhashCDC` instance.
+    pub fn with_table(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        table: [u32; 256],
+    ) -> Self {
+        Self::try_with_table(source, win_size, min_size, avg_size, max_size, table)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `BuzhashCDC` using a custom table, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `table` - Table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_with_table(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        table: [u32; 256],
+    ) -> Result<Self, super::ChunkerError> {
+        if !(MIN_WIN_SIZE..=MAX_WIN_SIZE).contains(&win_size) {
+            return Err(super::ChunkerError::WindowSize(win_size));
+        }
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        let out_table = table.map(|value| value.rotate_left((win_size % 32) as u32));
+
+        // Expected chunk length is the minimum size plus 2^bits.
+        let target = avg_size.saturating_sub(min_size).max(1);
+        let bits = (target as f64).log2().round() as u32;
+
+        Ok(Self {
+            table,
+            out_table,
+            win_size,
+            cur_pos: 0,
+            cut_mask: ((1u64 << bits) - 1) as u32,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// Only the last window before the minimum chunk size is hashed ahead of the first cut check,
+    /// since earlier bytes can't influence the hash.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+        let first = min_size.saturating_sub(self.win_size);
+
+        let mut hash = 0u32;
+        for (pos, &byte) in data.iter().enumerate().skip(first) {
+            hash = hash.rotate_left(1) ^ self.table[byte as usize];
+            if pos >= first + self.win_size {
+                hash ^= self.out_table[data[pos - self.win_size] as usize];
+            }
+
+            if pos + 1 >= min_size && hash & self.cut_mask == 0 {
+                return pos + 1;
+            }
+        }
+
+        limit
+    }
+}
+
+/// Configuration of the Buzhash chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BuzhashConfig {
+    /// Size of the sliding window for chunk determination.
+    pub win_size: usize,
+    /// Seed of the Buzhash table.
+    pub seed: u64,
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl BuzhashConfig {
+    /// Constructs a new `BuzhashConfig`.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `seed` - Seed of the Buzhash table.
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(win_size: usize, seed: u64, chunk_parms: super::ChunkSizeParms) -> Self {
+        Self {
+            win_size,
+            seed,
+            chunk_parms,
+        }
+    }
+
+    /// Constructs a new `BuzhashConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `seed` - Seed of the Buzhash table.
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        win_size: usize,
+        seed: u64,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        BuzhashCDC::try_new(
+            &[],
+            win_size,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(win_size, seed, chunk_parms))
+    }
+}
+
+impl super::Chunker for BuzhashConfig {
+    type Iter<'a> = BuzhashCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Buzhash Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        BuzhashCDC::with_seed(
+            source,
+            self.win_size,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+            self.seed,
+        )
+    }
+}
+
+impl Iterator for BuzhashCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Buzhash rolling hash.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the Buzhash of a window from scratch.
+    fn window_hash(table: &[u32; 256], window: &[u8]) -> u32 {
+        window.iter().enumerate().fold(0u32, |hash, (i, &byte)| {
+            hash ^ table[byte as usize].rotate_left(((window.len() - 1 - i) % 32) as u32)
+        })
+    }
+
+    /// Tests that the rolling hash of every cut position matches the hash of its window.
+    #[test]
+    fn test_cut_hash_matches_window() {
+        let data = crate::generate_data_block(512 * 1024, Some(31));
+        let table = buzhash_table(DEFAULT_BUZHASH_SEED);
+
+        for win_size in [8, 48, 64, 100] {
+            let chunks: Vec<_> = BuzhashCDC::new(&data, win_size, 512, 2048, 8192).collect();
+            for chunk in &chunks[..chunks.len() - 1] {
+                if chunk.length == 8192 {
+                    continue;
+                }
+                let end = chunk.offset + chunk.length;
+                let hash = window_hash(&table, &data[end - win_size..end]);
+                assert_eq!(hash & 2047, 0, "Window {} cut at {}", win_size, end);
+            }
+        }
+    }
+
+    /// Tests chunk invariants on random data.
+    #[test]
+    fn test_random_data_invariants() {
+        let data = crate::generate_data_block(1024 * 1024 + 3, Some(32));
+        let chunks: Vec<_> = BuzhashCDC::new(&data, 64, 1024, 4096, 16384).collect();
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 16384);
+            assert!(chunk.length >= 1024 || i + 1 == chunks.len());
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+    }
+
+    /// Tests that the seed changes the table and the boundaries.
+    #[test]
+    fn test_seeded_table() {
+        let data = crate::generate_data_block(256 * 1024, Some(33));
+
+        assert_eq!(buzhash_table(1), buzhash_table(1));
+        assert_ne!(buzhash_table(1), buzhash_table(2));
+
+        let chunks1: Vec<_> = BuzhashCDC::with_seed(&data, 64, 256, 1024, 4096, 1).collect();
+        let chunks2: Vec<_> = BuzhashCDC::with_seed(&data, 64, 256, 1024, 4096, 2).collect();
+        assert_ne!(chunks1, chunks2);
+    }
+
+    /// Tests BuzhashCDC with a window size that is too small.
+    #[test]
+    #[should_panic(expected = "Window size out of valid range")]
+    fn test_window_size_too_small() {
+        let _chunker = BuzhashCDC::new(&[], 4, 256, 1024, 4096);
+    }
+}
@@ -0,0 +1,359 @@
+//! Reading and writing of the `.caibx` chunk index format of casync and desync.
+//!
+//! An index starts with a header holding the feature flags and the chunk sizes, followed by a
+//! table of the chunks of a blob in order. Each table item holds the end offset of a chunk and its
+//! 32-byte ID, which is the SHA-512/256 digest of the uncompressed chunk data. The table is closed
+//! by a tail that allows locating the table from the end of the file. All numbers are little
+//! endian.
+
+use std::io::{self, Read, Write};
+
+use sha2::{Digest, Sha512_256};
+
+use super::{Chunk, ChunkSizeParms};
+
+/// Type of the index header.
+const CA_FORMAT_INDEX: u64 = 0x9682_4d9c_7b12_9ff9;
+/// Type of the chunk table header.
+const CA_FORMAT_TABLE: u64 = 0xe75b_9e11_2f17_417d;
+/// Marker at the end of the chunk table tail.
+const CA_FORMAT_TABLE_TAIL_MARKER: u64 = 0x4b4f_050e_5549_ecd1;
+
+/// Size of the index header.
+const INDEX_HEADER_SIZE: u64 = 48;
+/// Size of the chunk table header, which is followed by an unknown number of items.
+const TABLE_HEADER_SIZE: u64 = 16;
+/// Size of a table item, which is also the size of the table tail.
+const TABLE_ITEM_SIZE: u64 = 40;
+
+/// Feature flag telling that the chunk IDs are SHA-512/256 digests rather than SHA-256 ones.
+pub const CA_FORMAT_SHA512_256: u64 = 0x2000_0000_0000_0000;
+
+/// ID of a chunk, the digest of its data.
+pub type ChunkId = [u8; 32];
+
+/// Computes the ID of a chunk, the SHA-512/256 digest of its data.
+///
+/// # Arguments
+///
+/// * `data` - The uncompressed data of the chunk.
+pub fn chunk_id(data: &[u8]) -> ChunkId {
+    Sha512_256::digest(data).into()
+}
+
+/// Entry of a chunk index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct CaibxEntry {
+    /// Position and length of the chunk within the blob.
+    pub chunk: Chunk,
+    /// ID of the chunk.
+    pub id: ChunkId,
+}
+
+/// Chunk index of a blob, as stored in a `.caibx` file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CaibxIndex {
+    /// Feature flags of the index, which include [`CA_FORMAT_SHA512_256`] for SHA-512/256 IDs.
+    pub feature_flags: u64,
+    /// Chunk sizes the blob was chunked with.
+    pub chunk_parms: ChunkSizeParms,
+    /// Chunks of the blob in order.
+    pub entries: Vec<CaibxEntry>,
+}
+
+impl CaibxIndex {
+    /// Constructs an empty index with SHA-512/256 chunk IDs.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_parms` - Chunk sizes the blob is chunked with.
+    pub fn new(chunk_parms: ChunkSizeParms) -> Self {
+        Self {
+            feature_flags: CA_FORMAT_SHA512_256,
+            chunk_parms,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Constructs the index of chunks of a source.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk_parms` - Chunk sizes the source was chunked with.
+    /// * `source` - Data buffer that was chunked.
+    /// * `chunks` - Chunks of the source in order.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the chunks are not contiguous from the start of the source.
+    pub fn from_chunks(
+        chunk_parms: ChunkSizeParms,
+        source: &[u8],
+        chunks: impl IntoIterator<Item = Chunk>,
+    ) -> Self {
+        let mut index = Self::new(chunk_parms);
+        for chunk in chunks {
+            assert_eq!(chunk.offset, index.len(), "Chunks must be contiguous");
+            index.push(&source[chunk.offset..chunk.offset + chunk.length]);
+        }
+        index
+    }
+
+    /// Appends the chunk following the last one of the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The uncompressed data of the chunk.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the data is empty, as the format can't represent empty chunks.
+    pub fn push(&mut self, data: &[u8]) {
+        assert!(!data.is_empty(), "Chunk must not be empty");
+        let chunk = Chunk {
+            offset: self.len(),
+            length: data.len(),
+        };
+        self.entries.push(CaibxEntry {
+            chunk,
+            id: chunk_id(data),
+        });
+    }
+
+    /// Returns the size of the indexed blob.
+    pub fn len(&self) -> usize {
+        self.entries
+            .last()
+            .map_or(0, |entry| entry.chunk.offset + entry.chunk.length)
+    }
+
+    /// Returns `true` if the index has no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the index in the `.caibx` format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the index to.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let parms = &self.chunk_parms;
+        let mut header = Vec::with_capacity(INDEX_HEADER_SIZE as usize);
+        for value in [
+            INDEX_HEADER_SIZE,
+            CA_FORMAT_INDEX,
+            self.feature_flags,
+            parms.min_chunk_size as u64,
+            parms.avg_chunk_size as u64,
+            parms.max_chunk_size as u64,
+            u64::MAX,
+            CA_FORMAT_TABLE,
+        ] {
+            header.extend_from_slice(&value.to_le_bytes());
+        }
+        writer.write_all(&header)?;
+
+        for entry in &self.entries {
+            let end = (entry.chunk.offset + entry.chunk.length) as u64;
+            writer.write_all(&end.to_le_bytes())?;
+            writer.write_all(&entry.id)?;
+        }
+
+        let table_size = TABLE_HEADER_SIZE + (self.entries.len() as u64 + 1) * TABLE_ITEM_SIZE;
+        let mut tail = Vec::with_capacity(TABLE_ITEM_SIZE as usize);
+        for value in [
+            0,
+            0,
+            INDEX_HEADER_SIZE,
+            table_size,
+            CA_FORMAT_TABLE_TAIL_MARKER,
+        ] {
+            tail.extend_from_slice(&value.to_le_bytes());
+        }
+        writer.write_all(&tail)
+    }
+
+    /// Reads an index in the `.caibx` format.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to read the index from.
+    ///
+    /// # Returns
+    ///
+    /// The index, or an error of kind [`io::ErrorKind::InvalidData`] if the data is not a valid
+    /// chunk index.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut header = [0u8; (INDEX_HEADER_SIZE + TABLE_HEADER_SIZE) as usize];
+        reader.read_exact(&mut header)?;
+        let field = |i: usize| u64::from_le_bytes(header[i * 8..i * 8 + 8].try_into().unwrap());
+
+        if field(0) != INDEX_HEADER_SIZE || field(1) != CA_FORMAT_INDEX {
+            return Err(invalid_data("Missing chunk index header"));
+        }
+        if field(6) != u64::MAX || field(7) != CA_FORMAT_TABLE {
+            return Err(invalid_data("Missing chunk table header"));
+        }
+        let chunk_parms = ChunkSizeParms {
+            min_chunk_size: to_usize(field(3))?,
+            avg_chunk_size: to_usize(field(4))?,
+            max_chunk_size: to_usize(field(5))?,
+        };
+
+        let mut index = Self {
+            feature_flags: field(2),
+            chunk_parms,
+            entries: Vec::new(),
+        };
+
+        loop {
+            let mut item = [0u8; TABLE_ITEM_SIZE as usize];
+            reader.read_exact(&mut item)?;
+            let field = |i: usize| u64::from_le_bytes(item[i * 8..i * 8 + 8].try_into().unwrap());
+
+            // No chunk ends at offset zero, so a zero offset starts the tail.
+            let end = to_usize(field(0))?;
+            if end == 0 {
+                let table_size =
+                    TABLE_HEADER_SIZE + (index.entries.len() as u64 + 1) * TABLE_ITEM_SIZE;
+                if field(1) != 0
+                    || field(2) != INDEX_HEADER_SIZE
+                    || field(3) != table_size
+                    || field(4) != CA_FORMAT_TABLE_TAIL_MARKER
+                {
+                    return Err(invalid_data("Invalid chunk table tail"));
+                }
+                return Ok(index);
+            }
+
+            let offset = index.len();
+            if end <= offset {
+                return Err(invalid_data("Chunk table offsets must be increasing"));
+            }
+            index.entries.push(CaibxEntry {
+                chunk: Chunk {
+                    offset,
+                    length: end - offset,
+                },
+                id: item[8..].try_into().unwrap(),
+            });
+        }
+    }
+}
+
+/// Builds an error for malformed index data.
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Converts a size stored in an index to a `usize`.
+fn to_usize(value: u64) -> io::Result<usize> {
+    usize::try_from(value).map_err(|_| invalid_data("Size in chunk index too large"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CasyncConfig, Chunker, CASYNC_DEFAULT_PARMS};
+
+    /// Tests the chunk ID against the SHA-512/256 test vector of FIPS 180-4.
+    #[test]
+    fn test_chunk_id() {
+        let expected = "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23";
+        let hex: String = chunk_id(b"abc")
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(hex, expected);
+    }
+
+    /// Tests the byte layout of an index with a single chunk.
+    #[test]
+    fn test_layout() {
+        let mut index = CaibxIndex::new(CASYNC_DEFAULT_PARMS);
+        index.push(b"abc");
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+
+        let words = |range: std::ops::Range<usize>| -> Vec<u64> {
+            bytes[range]
+                .chunks(8)
+                .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+                .collect()
+        };
+        assert_eq!(bytes.len(), 48 + 16 + 40 + 40);
+        assert_eq!(
+            words(0..64),
+            [
+                48,
+                0x9682_4d9c_7b12_9ff9,
+                0x2000_0000_0000_0000,
+                16 * 1024,
+                64 * 1024,
+                256 * 1024,
+                u64::MAX,
+                0xe75b_9e11_2f17_417d,
+            ]
+        );
+        assert_eq!(words(64..72), [3]);
+        assert_eq!(bytes[72..104], chunk_id(b"abc"));
+        assert_eq!(words(104..144), [0, 0, 48, 96, 0x4b4f_050e_5549_ecd1]);
+    }
+
+    /// Tests that an index of chunked data is read back unchanged.
+    #[test]
+    fn test_round_trip() {
+        let data = crate::generate_data_block(2 * 1024 * 1024 + 11, Some(183));
+        let config = CasyncConfig::new(CASYNC_DEFAULT_PARMS);
+        let chunks: Vec<_> = config.chunks(&data).collect();
+        let index = CaibxIndex::from_chunks(config.chunk_parms(), &data, chunks.iter().copied());
+
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        let read = CaibxIndex::read_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(read, index);
+        assert_eq!(read.len(), data.len());
+        for (entry, chunk) in read.entries.iter().zip(&chunks) {
+            assert_eq!(entry.chunk, *chunk);
+            assert_eq!(
+                entry.id,
+                chunk_id(&data[chunk.offset..chunk.offset + chunk.length])
+            );
+        }
+    }
+
+    /// Tests that an empty index is read back unchanged.
+    #[test]
+    fn test_empty() {
+        let index = CaibxIndex::new(CASYNC_DEFAULT_PARMS);
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        assert_eq!(CaibxIndex::read_from(bytes.as_slice()).unwrap(), index);
+    }
+
+    /// Tests that corrupt and truncated indexes are rejected.
+    #[test]
+    fn test_invalid() {
+        let data = crate::generate_data_block(256 * 1024, Some(184));
+        let config = CasyncConfig::new(CASYNC_DEFAULT_PARMS);
+        let index = CaibxIndex::from_chunks(config.chunk_parms(), &data, config.chunks(&data));
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        let err = CaibxIndex::read_from(corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut corrupt = bytes.clone();
+        corrupt[8] ^= 1;
+        let err = CaibxIndex::read_from(corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let err = CaibxIndex::read_from(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
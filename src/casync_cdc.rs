@@ -0,0 +1,378 @@
+/// Size of the sliding window of casync.
+const WINDOW_SIZE: usize = 48;
+
+/// Default chunk sizes of casync, a quarter and four times the average of 64 KiB.
+pub const CASYNC_DEFAULT_PARMS: super::ChunkSizeParms = super::ChunkSizeParms {
+    min_chunk_size: 16 * 1024,
+    avg_chunk_size: 64 * 1024,
+    max_chunk_size: 256 * 1024,
+};
+
+/// Buzhash table of casync, `buzhash_table` in `src/cachunker.c`, also used by desync.
+pub const CASYNC_BUZHASH_TABLE: [u32; 256] = [
+    0x458be752, 0xc10748cc, 0xfbbcdbb8, 0x6ded5b68, 0xb10a82b5, 0x20d75648, 0xdfc5665f, 0xa8428801,
+    0x7ebf5191, 0x841135c7, 0x65cc53b3, 0x280a597c, 0x16f60255, 0xc78cbc3e, 0x294415f5, 0xb938d494,
+    0xec85c4e6, 0xb7d33edc, 0xe549b544, 0xfdeda5aa, 0x882bf287, 0x3116737c, 0x05569956, 0xe8cc1f68,
+    0x0806ac5e, 0x22a14443, 0x15297e10, 0x50d090e7, 0x4ba60f6f, 0xefd9f1a7, 0x5c5c885c, 0x82482f93,
+    0x9bfd7c64, 0x0b3e7276, 0xf2688e77, 0x8fad8abc, 0xb0509568, 0xf1ada29f, 0xa53efdfe, 0xcb2b1d00,
+    0xf2a9e986, 0x6463432b, 0x95094051, 0x5a223ad2, 0x9be8401b, 0x61e579cb, 0x1a556a14, 0x5840fdc2,
+    0x9261ddf6, 0xcde002bb, 0x52432bb0, 0xbf17373e, 0x7b7c222f, 0x2955ed16, 0x9f10ca59, 0xe840c4c9,
+    0xccabd806, 0x14543f34, 0x1462417a, 0x0d4a1f9c, 0x087ed925, 0xd7f8f24c, 0x7338c425, 0xcf86c8f5,
+    0xb19165cd, 0x9891c393, 0x325384ac, 0x0308459d, 0x86141d7e, 0xc922116a, 0xe2ffa6b6, 0x53f52aed,
+    0x2cd86197, 0xf5b9f498, 0xbf319c8f, 0xe0411fae, 0x977eb18c, 0xd8770976, 0x9833466a, 0xc674df7f,
+    0x8c297d45, 0x8ca48d26, 0xc49ed8e2, 0x7344f874, 0x556f79c7, 0x6b25eaed, 0xa03e2b42, 0xf68f66a4,
+    0x8e8b09a2, 0xf2e0e62a, 0x0d3a9806, 0x9729e493, 0x8c72b0fc, 0x160b94f6, 0x450e4d3d, 0x7a320e85,
+    0xbef8f0e1, 0x21d73653, 0x4e3d977a, 0x1e7b3929, 0x1cc6c719, 0xbe478d53, 0x8d752809, 0xe6d8c2c6,
+    0x275f0892, 0xc8acc273, 0x4cc21580, 0xecc4a617, 0xf5f7be70, 0xe795248a, 0x375a2fe9, 0x425570b6,
+    0x8898dcf8, 0xdc2d97c4, 0x0106114b, 0x364dc22f, 0x1e0cad1f, 0xbe63803c, 0x5f69fac2, 0x4d5afa6f,
+    0x1bc0dfb5, 0xfb273589, 0x0ea47f7b, 0x3c1c2b50, 0x21b2a932, 0x6b1223fd, 0x2fe706a8, 0xf9bd6ce2,
+    0xa268e64e, 0xe987f486, 0x3eacf563, 0x1ca2018c, 0x65e18228, 0x2207360a, 0x57cf1715, 0x34c37d2b,
+    0x1f8f3cde, 0x93b657cf, 0x31a019fd, 0xe69eb729, 0x8bca7b9b, 0x4c9d5bed, 0x277ebeaf, 0xe0d8f8ae,
+    0xd150821c, 0x31381871, 0xafc3f1b0, 0x927db328, 0xe95effac, 0x305a47bd, 0x426ba35b, 0x1233af3f,
+    0x686a5b83, 0x50e072e5, 0xd9d3bb2a, 0x8befc475, 0x487f0de6, 0xc88dff89, 0xbd664d5e, 0x971b5d18,
+    0x63b14847, 0xd7d3c1ce, 0x7f583cf3, 0x72cbcb09, 0xc0d0a81c, 0x7fa3429b, 0xe9158a1b, 0x225ea19a,
+    0xd8ca9ea3, 0xc763b282, 0xbb0c6341, 0x020b8293, 0xd4cd299d, 0x58cfa7f8, 0x91b4ee53, 0x37e4d140,
+    0x95ec764c, 0x30f76b06, 0x5ee68d24, 0x679c8661, 0xa41979c2, 0xf2b61284, 0x4fac1475, 0x0adb49f9,
+    0x19727a23, 0x15a7e374, 0xc43a18d5, 0x3fb1aa73, 0x342fc615, 0x924c0793, 0xbee2d7f0, 0x8a279de9,
+    0x4aa2d70c, 0xe24dd37f, 0xbe862c0b, 0x177c22c2, 0x5388e5ee, 0xcd8a7510, 0xf901b4fd, 0xdbc13dbc,
+    0x6c0bae5b, 0x64efe8c7, 0x48b02079, 0x80331a49, 0xca3d8ae6, 0xf3546190, 0xfed7108b, 0xc49b941b,
+    0x32baf4a9, 0xeb833a4a, 0x88a3f1a5, 0x3a91ce0a, 0x3cc27da1, 0x7112e684, 0x4a3096b1, 0x3794574c,
+    0xa3c8b6f3, 0x1d213941, 0x6e0a2e00, 0x233479f1, 0x0f4cd82f, 0x6093edd2, 0x5d7d209e, 0x464fe319,
+    0xd4dcac9e, 0x0db845cb, 0xfb5e4bc3, 0xe0256ce1, 0x09fb4ed1, 0x0914be1e, 0xa5bdb2c3, 0xc6eb57bb,
+    0x30320350, 0x3f397e91, 0xa67791bc, 0x86bc0e2c, 0xefa0a7e2, 0xe9ff7543, 0xe733612c, 0xd185897b,
+    0x329e5388, 0x91dd236b, 0x2ecb0d93, 0xf4d82a3d, 0x35b5c03f, 0xe4e606f0, 0x05b21843, 0x37b45964,
+    0x5eff22f4, 0x6027f4cc, 0x77178b3c, 0xae507131, 0x7bf7cabc, 0xf9c18d66, 0x593ade65, 0xd95ddf11,
+];
+
+/// Computes the discriminator of casync for an average chunk size.
+///
+/// A cut point is found where the hash modulo the discriminator is one less than the
+/// discriminator. The correction term of casync accounts for the minimum and maximum chunk sizes,
+/// so that the resulting chunks are close to the requested average.
+pub fn casync_discriminator(avg_chunk_size: usize) -> u32 {
+    let avg = avg_chunk_size as f64;
+    (avg / (-1.42888852e-7 * avg + 1.33237515)) as u32
+}
+
+/// Buzhash chunker following the chunker of casync and desync.
+///
+/// The hash covers a window of 48 bytes, which restarts at every chunk. A chunk ends after the
+/// first byte at or past the minimum chunk size where the hash modulo the discriminator is one
+/// less than the discriminator, or at the maximum chunk size.
+///
+/// The buzhash table of casync, [`CASYNC_BUZHASH_TABLE`], is used by default, and other tables
+/// are supported with [`CasyncCDC::with_table`]. The boundaries haven't been checked against the
+/// output of casync or desync.
+pub struct CasyncCDC<'a> {
+    /// Table mapping each byte value to a pseudo-random 32-bit number.
+    table: [u32; 256],
+    /// Table values rotated by the window size, removing the byte leaving the window.
+    out_table: [u32; 256],
+    /// Divisor of the hash used to determine chunk cuts.
+    discriminator: u32,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> CasyncCDC<'a> {
+    /// Constructs a new `CasyncCDC` using [`CASYNC_BUZHASH_TABLE`].
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `CasyncCDC` instance.
+    pub fn new(source: &'a [u8], chunk_parms: super::ChunkSizeParms) -> Self {
+        Self::with_table(source, chunk_parms, CASYNC_BUZHASH_TABLE)
+    }
+
+    /// Constructs a new `CasyncCDC` using [`CASYNC_BUZHASH_TABLE`], checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_table(source, chunk_parms, CASYNC_BUZHASH_TABLE)
+    }
+
+    /// Constructs a new `CasyncCDC` using a custom table.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    /// * `table` - Table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `CasyncCDC` instance.
+    pub fn with_table(
+        source: &'a [u8],
+        chunk_parms: super::ChunkSizeParms,
+        table: [u32; 256],
+    ) -> Self {
+        Self::try_with_table(source, chunk_parms, table).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `CasyncCDC` using a custom table, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    /// * `table` - Table mapping each byte value to a pseudo-random 32-bit number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_with_table(
+        source: &'a [u8],
+        chunk_parms: super::ChunkSizeParms,
+        table: [u32; 256],
+    ) -> Result<Self, super::ChunkerError> {
+        super::ChunkSizeParms::new(
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+
+        let rotation = (WINDOW_SIZE % 32) as u32;
+
+        Ok(Self {
+            table,
+            out_table: table.map(|value| value.rotate_left(rotation)),
+            discriminator: casync_discriminator(chunk_parms.avg_chunk_size),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// The window is rolled from scratch at the start of every chunk, so the hash at the minimum
+    /// chunk size only covers the bytes of the chunk before it.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+        let target = self.discriminator - 1;
+
+        let mut pos = min_size;
+        let mut hash = data[pos - WINDOW_SIZE..pos]
+            .iter()
+            .fold(0u32, |hash, &byte| {
+                hash.rotate_left(1) ^ self.table[byte as usize]
+            });
+
+        while pos < limit {
+            if hash % self.discriminator == target {
+                return pos;
+            }
+
+            hash = hash.rotate_left(1)
+                ^ self.out_table[data[pos - WINDOW_SIZE] as usize]
+                ^ self.table[data[pos] as usize];
+            pos += 1;
+        }
+
+        limit
+    }
+}
+
+/// Configuration of the casync chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CasyncConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+    /// Table mapping each byte value to a pseudo-random 32-bit number.
+    pub table: [u32; 256],
+}
+
+impl CasyncConfig {
+    /// Constructs a new `CasyncConfig` using [`CASYNC_BUZHASH_TABLE`].
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self::with_table(chunk_parms, CASYNC_BUZHASH_TABLE)
+    }
+
+    /// Constructs a new `CasyncConfig` using [`CASYNC_BUZHASH_TABLE`], checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        CasyncCDC::try_new(&[], chunk_parms)?;
+        Ok(Self::new(chunk_parms))
+    }
+
+    /// Constructs a new `CasyncConfig` using a custom table.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Parameters specifying minimum, average, and maximum chunk sizes.
+    /// * `table` - Table mapping each byte value to a pseudo-random 32-bit number.
+    pub fn with_table(chunk_parms: super::ChunkSizeParms, table: [u32; 256]) -> Self {
+        Self { chunk_parms, table }
+    }
+}
+
+impl super::Chunker for CasyncConfig {
+    type Iter<'a> = CasyncCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Casync Buzhash Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        CasyncCDC::with_table(source, self.chunk_parms, self.table)
+    }
+}
+
+impl Iterator for CasyncCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Buzhash rolling hash.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the hash of the window ending at `end` from scratch, like casync does when a
+    /// window has been filled.
+    fn window_hash(data: &[u8], end: usize, table: &[u32; 256]) -> u32 {
+        let window = &data[end - WINDOW_SIZE..end];
+        window.iter().enumerate().fold(0, |hash, (i, &byte)| {
+            hash ^ table[byte as usize].rotate_left(((WINDOW_SIZE - i - 1) % 32) as u32)
+        })
+    }
+
+    /// Tests that every bit is set in exactly half of the values of the casync table, like in the
+    /// table of casync, which catches any mistyped value.
+    #[test]
+    fn test_table_balanced() {
+        for bit in 0..32 {
+            let count = CASYNC_BUZHASH_TABLE
+                .iter()
+                .filter(|&&value| value >> bit & 1 == 1)
+                .count();
+            assert_eq!(count, 128, "Bit {}", bit);
+        }
+    }
+
+    /// Tests the discriminators for a few average sizes.
+    #[test]
+    fn test_discriminator() {
+        assert_eq!(casync_discriminator(64 * 1024), 49535);
+        assert_eq!(casync_discriminator(16 * 1024), 12318);
+        assert_eq!(casync_discriminator(1024 * 1024), 886_711);
+    }
+
+    /// Tests that every content defined chunk ends where the hash of its last window matches,
+    /// and that no earlier position past the minimum size matches.
+    #[test]
+    fn test_cut_points() {
+        let parms = crate::ChunkSizeParms {
+            min_chunk_size: 1024,
+            avg_chunk_size: 4096,
+            max_chunk_size: 16384,
+        };
+        let data = crate::generate_data_block(512 * 1024, Some(181));
+        let chunker = CasyncCDC::new(&data, parms);
+        let (table, discriminator) = (chunker.table, chunker.discriminator);
+        let matches = |end| window_hash(&data, end, &table) % discriminator == discriminator - 1;
+
+        let chunks: Vec<_> = chunker.collect();
+        for chunk in &chunks[..chunks.len() - 1] {
+            let end = chunk.offset + chunk.length;
+            assert!(
+                (chunk.offset + parms.min_chunk_size..end).all(|pos| !matches(pos)),
+                "Earlier cut point in chunk at {}",
+                chunk.offset
+            );
+            assert!(
+                chunk.length == parms.max_chunk_size || matches(end),
+                "Chunk at {} doesn't end at a cut point",
+                chunk.offset
+            );
+        }
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.length).sum::<usize>(),
+            data.len()
+        );
+    }
+
+    /// Tests that the average chunk size of the default parameters is close to the requested one.
+    #[test]
+    fn test_average_size() {
+        let data = crate::generate_data_block(16 * 1024 * 1024, Some(182));
+        let count = CasyncCDC::new(&data, CASYNC_DEFAULT_PARMS).count();
+        let avg = data.len() / count;
+        assert!(
+            (56 * 1024..72 * 1024).contains(&avg),
+            "Average chunk size {} too far from 64 KiB",
+            avg
+        );
+    }
+
+    /// Tests that a run of zeros is cut at the maximum chunk size.
+    #[test]
+    fn test_zeros() {
+        let data = vec![0u8; 1024 * 1024];
+        let lengths: Vec<_> = CasyncCDC::new(&data, CASYNC_DEFAULT_PARMS)
+            .map(|chunk| chunk.length)
+            .collect();
+        assert_eq!(lengths, [CASYNC_DEFAULT_PARMS.max_chunk_size; 4]);
+    }
+}
@@ -0,0 +1,680 @@
+use super::gear_simd::{vector_find_cut, FindCut};
+
+/// Masks for each of the desired number of bits, where 0 through 5 are unused.
+///
+/// The values are taken from the `fastcdc::v2020` implementation, which spreads the mask bits
+/// relatively evenly as the FastCDC paper claims it slightly improves the deduplication ratio.
+#[rustfmt::skip]
+pub(crate) const MASKS: [u64; 26] = [
+    0,                  // padding
+    0,                  // padding
+    0,                  // padding
+    0,                  // padding
+    0,                  // padding
+    0x0000000001804110, // unused except for NC 3
+    0x0000000001803110, // 64B
+    0x0000000018035100, // 128B
+    0x0000001800035300, // 256B
+    0x0000019000353000, // 512B
+    0x0000590003530000, // 1KB
+    0x0000d90003530000, // 2KB
+    0x0000d90103530000, // 4KB
+    0x0000d90303530000, // 8KB
+    0x0000d90313530000, // 16KB
+    0x0000d90f03530000, // 32KB
+    0x0000d90303537000, // 64KB
+    0x0000d90703537000, // 128KB
+    0x0000d90707537000, // 256KB
+    0x0000d91707537000, // 512KB
+    0x0000d91747537000, // 1MB
+    0x0000d91767537000, // 2MB
+    0x0000d93767537000, // 4MB
+    0x0000d93777537000, // 8MB
+    0x0000d93777577000, // 16MB
+    0x0000db3777577000, // unused except for NC 3
+];
+
+/// Gear table, where each byte value is mapped to a pseudo-random 64-bit number.
+///
+/// The values are the same as the ones used by the `fastcdc` crate, so both implementations
+/// produce the same boundaries.
+#[rustfmt::skip]
+pub(crate) const GEAR: [u64; 256] = [
+    0x3b5d3c7d207e37dc, 0x784d68ba91123086, 0xcd52880f882e7298, 0xeacf8e4e19fdcca7,
+    0xc31f385dfbd1632b, 0x1d5f27001e25abe6, 0x83130bde3c9ad991, 0xc4b225676e9b7649,
+    0xaa329b29e08eb499, 0xb67fcbd21e577d58, 0x0027baaada2acf6b, 0xe3ef2d5ac73c2226,
+    0x0890f24d6ed312b7, 0xa809e036851d7c7e, 0xf0a6fe5e0013d81b, 0x1d026304452cec14,
+    0x03864632648e248f, 0xcdaacf3dcd92b9b4, 0xf5e012e63c187856, 0x8862f9d3821c00b6,
+    0xa82f7338750f6f8a, 0x1e583dc6c1cb0b6f, 0x7a3145b69743a7f1, 0xabb20fee404807eb,
+    0xb14b3cfe07b83a5d, 0xb9dc27898adb9a0f, 0x3703f5e91baa62be, 0xcf0bb866815f7d98,
+    0x3d9867c41ea9dcd3, 0x1be1fa65442bf22c, 0x14300da4c55631d9, 0xe698e9cbc6545c99,
+    0x4763107ec64e92a5, 0xc65821fc65696a24, 0x76196c064822f0b7, 0x485be841f3525e01,
+    0xf652bc9c85974ff5, 0xcad8352face9e3e9, 0x2a6ed1dceb35e98e, 0xc6f483badc11680f,
+    0x3cfd8c17e9cf12f1, 0x89b83c5e2ea56471, 0xae665cfd24e392a9, 0xec33c4e504cb8915,
+    0x3fb9b15fc9fe7451, 0xd7fd1fd1945f2195, 0x31ade0853443efd8, 0x255efc9863e1e2d2,
+    0x10eab6008d5642cf, 0x46f04863257ac804, 0xa52dc42a789a27d3, 0xdaaadf9ce77af565,
+    0x6b479cd53d87febb, 0x6309e2d3f93db72f, 0xc5738ffbaa1ff9d6, 0x6bd57f3f25af7968,
+    0x67605486d90d0a4a, 0xe14d0b9663bfbdae, 0xb7bbd8d816eb0414, 0xdef8a4f16b35a116,
+    0xe7932d85aaaffed6, 0x08161cbae90cfd48, 0x855507beb294f08b, 0x91234ea6ffd399b2,
+    0xad70cf4b2435f302, 0xd289a97565bc2d27, 0x8e558437ffca99de, 0x96d2704b7115c040,
+    0x0889bbcdfc660e41, 0x5e0d4e67dc92128d, 0x72a9f8917063ed97, 0x438b69d409e016e3,
+    0xdf4fed8a5d8a4397, 0x00f41dcf41d403f7, 0x4814eb038e52603f, 0x9dafbacc58e2d651,
+    0xfe2f458e4be170af, 0x4457ec414df6a940, 0x06e62f1451123314, 0xbd1014d173ba92cc,
+    0xdef318e25ed57760, 0x9fea0de9dfca8525, 0x459de1e76c20624b, 0xaeec189617e2d666,
+    0x126a2c06ab5a83cb, 0xb1321532360f6132, 0x65421503dbb40123, 0x2d67c287ea089ab3,
+    0x6c93bff5a56bd6b6, 0x4ffb2036cab6d98d, 0xce7b785b1be7ad4f, 0xedb42ef6189fd163,
+    0xdc905288703988f6, 0x365f9c1d2c691884, 0xc640583680d99bfe, 0x3cd4624c07593ec6,
+    0x7f1ea8d85d7c5805, 0x014842d480b57149, 0x0b649bcb5a828688, 0xbcd5708ed79b18f0,
+    0xe987c862fbd2f2f0, 0x982731671f0cd82c, 0xbaf13e8b16d8c063, 0x8ea3109cbd951bba,
+    0xd141045bfb385cad, 0x2acbc1a0af1f7d30, 0xe6444d89df03bfdf, 0xa18cc771b8188ff9,
+    0x9834429db01c39bb, 0x214add07fe086a1f, 0x8f07c19b1f6b3ff9, 0x56a297b1bf4ffe55,
+    0x94d558e493c54fc7, 0x40bfc24c764552cb, 0x931a706f8a8520cb, 0x32229d322935bd52,
+    0x2560d0f5dc4fefaf, 0x9dbcc48355969bb6, 0x0fd81c3985c0b56a, 0xe03817e1560f2bda,
+    0xc1bb4f81d892b2d5, 0xb0c4864f4e28d2d7, 0x3ecc49f9d9d6c263, 0x51307e99b52ba65e,
+    0x8af2b688da84a752, 0xf5d72523b91b20b6, 0x6d95ff1ff4634806, 0x562f21555458339a,
+    0xc0ce47f889336346, 0x487823e5089b40d8, 0xe4727c7ebc6d9592, 0x5a8f7277e94970ba,
+    0xfca2f406b1c8bb50, 0x5b1f8a95f1791070, 0xd304af9fc9028605, 0x5440ab7fc930e748,
+    0x312d25fbca2ab5a1, 0x10f4a4b234a4d575, 0x90301d55047e7473, 0x3b6372886c61591e,
+    0x293402b77c444e06, 0x451f34a4d3e97dd7, 0x3158d814d81bc57b, 0x034942425b9bda69,
+    0xe2032ff9e532d9bb, 0x62ae066b8b2179e5, 0x9545e10c2f8d71d8, 0x7ff7483eb2d23fc0,
+    0x00945fcebdc98d86, 0x8764bbbe99b26ca2, 0x1b1ec62284c0bfc3, 0x58e0fcc4f0aa362b,
+    0x5f4abefa878d458d, 0xfd74ac2f9607c519, 0xa4e3fb37df8cbfa9, 0xbf697e43cac574e5,
+    0x86f14a3f68f4cd53, 0x24a23d076f1ce522, 0xe725cd8048868cc8, 0xbf3c729eb2464362,
+    0xd8f6cd57b3cc1ed8, 0x6329e52425541577, 0x62aa688ad5ae1ac0, 0x0a242566269bf845,
+    0x168b1a4753aca74b, 0xf789afefff2e7e3c, 0x6c3362093b6fccdb, 0x4ce8f50bd28c09b2,
+    0x006a2db95ae8aa93, 0x975b0d623c3d1a8c, 0x18605d3935338c5b, 0x5bb6f6136cad3c71,
+    0x0f53a20701f8d8a6, 0xab8c5ad2e7e93c67, 0x40b5ac5127acaa29, 0x8c7bf63c2075895f,
+    0x78bd9f7e014a805c, 0xb2c9e9f4f9c8c032, 0xefd6049827eb91f3, 0x2be459f482c16fbd,
+    0xd92ce0c5745aaa8c, 0x0aaa8fb298d965b9, 0x2b37f92c6c803b15, 0x8c54a5e94e0f0e78,
+    0x95f9b6e90c0a3032, 0xe7939faa436c7874, 0xd16bfe8f6a8a40c9, 0x44982b86263fd2fa,
+    0xe285fb39f984e583, 0x779a8df72d7619d3, 0xf2d79a8de8d5dd1e, 0xd1037354d66684e2,
+    0x004c82a4e668a8e5, 0x31d40a7668b044e6, 0xd70578538bd02c11, 0xdb45431078c5f482,
+    0x977121bb7f6a51ad, 0x73d5ccbd34eff8dd, 0xe437a07d356e17cd, 0x47b2782043c95627,
+    0x9fb251413e41d49a, 0xccd70b60652513d3, 0x1c95b31e8a1b49b2, 0xcae73dfd1bcb4c1b,
+    0x34d98331b1f5b70f, 0x784e39f22338d92f, 0x18613d4a064df420, 0xf1d8dae25f0bcebe,
+    0x33f77c15ae855efc, 0x3c88b3b912eb109c, 0x956a2ec96bafeea5, 0x1aa005b5e0ad0e87,
+    0x5500d70527c4bb8e, 0xe36c57196421cc44, 0x13c4d286cc36ee39, 0x5654a23d818b2a81,
+    0x77b1dc13d161abdc, 0x734f44de5f8d5eb5, 0x60717e174a6c89a2, 0xd47d9649266a211e,
+    0x5b13a4322bb69e90, 0xf7669609f8b5fc3c, 0x21e6ac55bedcdac9, 0x9b56b62b61166dea,
+    0xf48f66b939797e9c, 0x35f332f9c0e6ae9a, 0xcc733f6a9a878db0, 0x3da161e41cc108c2,
+    0xb7d74ae535914d51, 0x4d493b0b11d36469, 0xce264d1dfba9741a, 0xa9d1f2dc7436dc06,
+    0x70738016604c2a27, 0x231d36e96e93f3d5, 0x7666881197838d19, 0x4a2a83090aaad40c,
+    0xf1e761591668b35d, 0x7363236497f730a7, 0x301080e37379dd4d, 0x502dea2971827042,
+    0xc2c5eb858f32625f, 0x786afb9edfafbdff, 0xdaee0d868490b2a4, 0x617366b3268609f6,
+    0xae0e35a0fe46173e, 0xd1a07de93e824f11, 0x079b8b115ea4cca8, 0x93a99274558faebb,
+    0xfb1e6e22e08a03b3, 0xea635fdba3698dd0, 0xcf53659328503a5c, 0xcde3b31e6fd5d780,
+    0x8e3e4221d3614413, 0xef14d0d86bf1a22c, 0xe1d830d3f16c5ddb, 0xaabd2b2a451504e1,
+];
+
+/// Gear table shifted left by one bit, used to roll two bytes at once.
+const GEAR_LS: [u64; 256] = shift_table(&GEAR);
+
+/// Level of the normalized chunking.
+///
+/// Normalization makes the chunk judgement harder before the average size is reached and easier
+/// after it, so higher levels produce chunk sizes that are more concentrated around the average.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Normalization {
+    /// No normalization, produces a wide range of chunk sizes.
+    Level0,
+    /// Fewer chunks are outside of the desired range.
+    Level1,
+    /// Most chunks are of the desired size.
+    Level2,
+    /// Nearly all chunks are of the desired size.
+    Level3,
+}
+
+impl Normalization {
+    /// Returns the number of bits the masks are shifted by.
+    pub fn bits(&self) -> u32 {
+        match self {
+            Normalization::Level0 => 0,
+            Normalization::Level1 => 1,
+            Normalization::Level2 => 2,
+            Normalization::Level3 => 3,
+        }
+    }
+}
+
+/// Revision of the FastCDC algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FastCDCVersion {
+    /// Algorithm from the 2016 paper, rolling one byte at a time.
+    V2016,
+    /// Algorithm from the 2020 paper, rolling two bytes at a time.
+    V2020,
+}
+
+/// FastCDC chunker for data segmentation based on normalized Gear chunking.
+pub struct FastCDC<'a> {
+    /// Mask used to determine chunk cuts before the average size is reached.
+    mask_s: u64,
+    /// Mask used to determine chunk cuts after the average size is reached.
+    mask_l: u64,
+    /// Revision of the algorithm used for the cut-point search.
+    version: FastCDCVersion,
+    /// Vectorized cut-point search, if enabled and supported by the CPU.
+    vector_search: Option<FindCut>,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> FastCDC<'a> {
+    /// Constructs a new `FastCDC` using the 2020 algorithm with level 1 normalization.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `FastCDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::with_level(source, min_size, avg_size, max_size, Normalization::Level1)
+    }
+
+    /// Constructs a new `FastCDC` using the 2020 algorithm with level 1 normalization,
+    /// checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_version(
+            source,
+            min_size,
+            avg_size,
+            max_size,
+            Normalization::Level1,
+            FastCDCVersion::V2020,
+        )
+    }
+
+    /// Constructs a new `FastCDC` using the 2020 algorithm with the given normalization level.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `level` - Level of the normalized chunking.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `FastCDC` instance.
+    pub fn with_level(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+    ) -> Self {
+        Self::with_version(
+            source,
+            min_size,
+            avg_size,
+            max_size,
+            level,
+            FastCDCVersion::V2020,
+        )
+    }
+
+    /// Constructs a new `FastCDC` using the given algorithm revision and normalization level.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `level` - Level of the normalized chunking.
+    /// * `version` - Revision of the algorithm.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `FastCDC` instance.
+    pub fn with_version(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        version: FastCDCVersion,
+    ) -> Self {
+        Self::try_with_version(source, min_size, avg_size, max_size, level, version)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `FastCDC` using the given algorithm revision and normalization level,
+    /// checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `level` - Level of the normalized chunking.
+    /// * `version` - Revision of the algorithm.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_with_version(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        level: Normalization,
+        version: FastCDCVersion,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        let bits = (avg_size as f64).log2().round() as u32;
+
+        Ok(Self {
+            mask_s: MASKS[(bits + level.bits()) as usize],
+            mask_l: MASKS[(bits - level.bits()) as usize],
+            version,
+            vector_search: vector_find_cut(),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Enables or disables the vectorized cut-point search.
+    ///
+    /// The vectorized search is enabled by default if the CPU supports AVX2 or AVX-512, and finds
+    /// the same cut points as the scalar one.
+    ///
+    /// # Arguments
+    /// * `enabled` - Whether to use the vectorized search when the CPU supports it.
+    ///
+    /// # Returns
+    ///
+    /// The updated `FastCDC` instance.
+    pub fn vectorized(mut self, enabled: bool) -> Self {
+        self.vector_search = vector_find_cut().filter(|_| enabled);
+        self
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// Sub-minimum cut-points are skipped, the "harder" mask is used until the average size is
+    /// reached and the "easier" one afterwards. The byte matching the mask starts the next chunk.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        if remaining <= self.chunk_parms.min_chunk_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let center = self.chunk_parms.avg_chunk_size.min(limit);
+        let data = &data[..limit];
+
+        if let Some(vector_search) = self.vector_search {
+            // Rolling two bytes at once is the same as rolling one byte at a time, apart from the
+            // search being confined to pairs of bytes.
+            let min_size = self.chunk_parms.min_chunk_size;
+            let (start, center, end) = match self.version {
+                FastCDCVersion::V2016 => (min_size, center, limit),
+                FastCDCVersion::V2020 => {
+                    let end = limit / 2 * 2;
+                    (min_size.next_multiple_of(2).min(end), center / 2 * 2, end)
+                }
+            };
+            return vector_search(data, start, center, end, self.mask_s, self.mask_l)
+                .unwrap_or(limit);
+        }
+
+        match self.version {
+            FastCDCVersion::V2016 => self.cut_2016(data, center),
+            FastCDCVersion::V2020 => self.cut_2020(data, center),
+        }
+    }
+
+    /// Cut-point search of the 2016 paper, rolling one byte at a time.
+    fn cut_2016(&self, data: &[u8], center: usize) -> usize {
+        let mut hash = 0u64;
+        let mut pos = self.chunk_parms.min_chunk_size;
+        while pos < center {
+            hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+            if hash & self.mask_s == 0 {
+                return pos;
+            }
+            pos += 1;
+        }
+        while pos < data.len() {
+            hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+            if hash & self.mask_l == 0 {
+                return pos;
+            }
+            pos += 1;
+        }
+
+        data.len()
+    }
+
+    /// Cut-point search of the 2020 paper, rolling two bytes at a time.
+    ///
+    /// The search starts at the first pair at or past the minimum chunk size, so an odd minimum
+    /// doesn't allow a cut one byte before it.
+    fn cut_2020(&self, data: &[u8], center: usize) -> usize {
+        let (mask_s_ls, mask_l_ls) = (self.mask_s << 1, self.mask_l << 1);
+
+        let mut hash = 0u64;
+        let mut index = self.chunk_parms.min_chunk_size.div_ceil(2);
+        while index < center / 2 {
+            let pos = index * 2;
+            hash = (hash << 2).wrapping_add(GEAR_LS[data[pos] as usize]);
+            if hash & mask_s_ls == 0 {
+                return pos;
+            }
+            hash = hash.wrapping_add(GEAR[data[pos + 1] as usize]);
+            if hash & self.mask_s == 0 {
+                return pos + 1;
+            }
+            index += 1;
+        }
+        while index < data.len() / 2 {
+            let pos = index * 2;
+            hash = (hash << 2).wrapping_add(GEAR_LS[data[pos] as usize]);
+            if hash & mask_l_ls == 0 {
+                return pos;
+            }
+            hash = hash.wrapping_add(GEAR[data[pos + 1] as usize]);
+            if hash & self.mask_l == 0 {
+                return pos + 1;
+            }
+            index += 1;
+        }
+
+        data.len()
+    }
+}
+
+/// Configuration of the FastCDC chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FastCDCConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+    /// Level of the normalized chunking.
+    pub level: Normalization,
+    /// Revision of the algorithm.
+    pub version: FastCDCVersion,
+    /// Whether to use the vectorized cut-point search when the CPU supports it.
+    pub vectorized: bool,
+}
+
+impl FastCDCConfig {
+    /// Constructs a new `FastCDCConfig` for the 2020 algorithm.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    /// * `level` - Level of the normalized chunking.
+    pub fn new(chunk_parms: super::ChunkSizeParms, level: Normalization) -> Self {
+        Self {
+            chunk_parms,
+            level,
+            version: FastCDCVersion::V2020,
+            vectorized: true,
+        }
+    }
+
+    /// Constructs a new `FastCDCConfig` for the 2020 algorithm, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    /// * `level` - Level of the normalized chunking.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        chunk_parms: super::ChunkSizeParms,
+        level: Normalization,
+    ) -> Result<Self, super::ChunkerError> {
+        FastCDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms, level))
+    }
+}
+
+impl super::Chunker for FastCDCConfig {
+    type Iter<'a> = FastCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Native Fast Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        FastCDC::with_version(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+            self.level,
+            self.version,
+        )
+        .vectorized(self.vectorized)
+    }
+}
+
+impl Iterator for FastCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the normalized Gear hash judgement.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+/// Shifts every value of the gear table left by one bit.
+const fn shift_table(table: &[u64; 256]) -> [u64; 256] {
+    let mut shifted = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        shifted[i] = table[i] << 1;
+        i += 1;
+    }
+    shifted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// All normalization levels.
+    const LEVELS: [Normalization; 4] = [
+        Normalization::Level0,
+        Normalization::Level1,
+        Normalization::Level2,
+        Normalization::Level3,
+    ];
+
+    /// Chunk size parameters used by the parity tests.
+    const PARMS: [(usize, usize, usize); 4] = [
+        (64, 256, 1024),
+        (2048, 8192, 65536),
+        (8192, 10240, 65536),
+        (16385, 32768, 131_072),
+    ];
+
+    /// Converts the normalization level to the one of the `fastcdc` crate.
+    fn v2020_level(level: Normalization) -> fastcdc::v2020::Normalization {
+        match level {
+            Normalization::Level0 => fastcdc::v2020::Normalization::Level0,
+            Normalization::Level1 => fastcdc::v2020::Normalization::Level1,
+            Normalization::Level2 => fastcdc::v2020::Normalization::Level2,
+            Normalization::Level3 => fastcdc::v2020::Normalization::Level3,
+        }
+    }
+
+    /// Converts the normalization level to the one of the `fastcdc` crate.
+    fn v2016_level(level: Normalization) -> fastcdc::v2016::Normalization {
+        match level {
+            Normalization::Level0 => fastcdc::v2016::Normalization::Level0,
+            Normalization::Level1 => fastcdc::v2016::Normalization::Level1,
+            Normalization::Level2 => fastcdc::v2016::Normalization::Level2,
+            Normalization::Level3 => fastcdc::v2016::Normalization::Level3,
+        }
+    }
+
+    /// Tests that the 2020 algorithm matches `fastcdc::v2020` for every normalization level.
+    #[test]
+    fn test_v2020_parity() {
+        let data = crate::generate_data_block(2 * 1024 * 1024, Some(0xFA57));
+
+        for (min, avg, max) in PARMS {
+            for level in LEVELS {
+                // The crate starts the search one byte before an odd minimum.
+                let expected: Vec<_> = fastcdc::v2020::FastCDC::with_level(
+                    &data,
+                    min.next_multiple_of(2) as u32,
+                    avg as u32,
+                    max as u32,
+                    v2020_level(level),
+                )
+                .map(|chunk| (chunk.offset, chunk.length))
+                .collect();
+                let chunks: Vec<_> = FastCDC::with_level(&data, min, avg, max, level)
+                    .map(|chunk| (chunk.offset, chunk.length))
+                    .collect();
+
+                assert_eq!(chunks, expected, "{:?} {} {} {}", level, min, avg, max);
+            }
+        }
+    }
+
+    /// Tests that the 2016 algorithm matches `fastcdc::v2016` for every normalization level.
+    #[test]
+    fn test_v2016_parity() {
+        let data = crate::generate_data_block(2 * 1024 * 1024, Some(0xFA57));
+
+        for (min, avg, max) in PARMS {
+            for level in LEVELS {
+                let expected: Vec<_> = fastcdc::v2016::FastCDC::with_level(
+                    &data,
+                    min as u32,
+                    avg as u32,
+                    max as u32,
+                    v2016_level(level),
+                )
+                .map(|chunk| (chunk.offset, chunk.length))
+                .collect();
+                let chunks: Vec<_> =
+                    FastCDC::with_version(&data, min, avg, max, level, FastCDCVersion::V2016)
+                        .map(|chunk| (chunk.offset, chunk.length))
+                        .collect();
+
+                assert_eq!(chunks, expected, "{:?} {} {} {}", level, min, avg, max);
+            }
+        }
+    }
+
+    /// Tests that each vectorized search supported by the CPU finds the same cut points as the
+    /// scalar one.
+    #[test]
+    fn test_vectorized_matches_scalar() {
+        let mut data = crate::generate_data_block(512 * 1024 + 1, Some(0x5EED));
+        data[100_000..200_000].fill(0);
+
+        let searches = crate::gear_simd::supported_searches();
+
+        for (min, avg, max) in [(65, 257, 1025), (2049, 8191, 65537), (8192, 10240, 65536)] {
+            for version in [FastCDCVersion::V2016, FastCDCVersion::V2020] {
+                for level in LEVELS {
+                    let chunker = || FastCDC::with_version(&data, min, avg, max, level, version);
+                    let expected: Vec<_> = chunker().vectorized(false).collect();
+
+                    for &(_, search) in &searches {
+                        let mut vectorized = chunker();
+                        vectorized.vector_search = Some(search);
+                        let chunks: Vec<_> = vectorized.collect();
+                        assert_eq!(
+                            chunks, expected,
+                            "{:?} {:?} {} {}",
+                            version, level, min, avg
+                        );
+                    }
+                }
+            }
+        }
+        crate::gear_simd::check_tested_searches("test_vectorized_matches_scalar", &searches);
+    }
+
+    /// Tests that a higher normalization level narrows the spread of chunk sizes.
+    #[test]
+    fn test_normalization_narrows_sizes() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(3));
+
+        let spread = |level| {
+            let sizes: Vec<_> = FastCDC::with_level(&data, 1024, 8192, 65536, level)
+                .map(|chunk| chunk.length as f64)
+                .collect();
+            let mean = sizes.iter().sum::<f64>() / sizes.len() as f64;
+            sizes.iter().map(|size| (size - mean).powi(2)).sum::<f64>() / sizes.len() as f64
+        };
+
+        assert!(spread(Normalization::Level3) < spread(Normalization::Level0));
+    }
+
+    /// Tests FastCDC with a minimum chunk size that is too small.
+    #[test]
+    #[should_panic(expected = "Min chunk size out of valid range")]
+    fn test_min_size_too_small() {
+        let _chunker = FastCDC::new(&[], 32, 256, 1024);
+    }
+}
@@ -19,14 +19,87 @@ impl<'a> FixedSizeChunking<'a> {
     ///
     /// Panic if `chunk_size` is zero, as this would not allow for any meaningful chunking.
     pub fn new(source: &'a [u8], chunk_size: usize) -> Self {
-        assert!(chunk_size > 0, "Chunk size must be greater than zero");
+        Self::try_new(source, chunk_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `FixedSizeChunking`, checking the chunk size.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `chunk_size` - Fixed size of each chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChunkerError::ZeroChunkSize`](super::ChunkerError::ZeroChunkSize) if
+    /// `chunk_size` is zero.
+    pub fn try_new(source: &'a [u8], chunk_size: usize) -> Result<Self, super::ChunkerError> {
+        if chunk_size == 0 {
+            return Err(super::ChunkerError::ZeroChunkSize);
+        }
 
-        Self {
+        Ok(Self {
             source,
             chunk_size,
             current_index: 0,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        data.len().min(self.chunk_size)
+    }
+}
+
+/// Configuration of the fixed size chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FixedSizeConfig {
+    /// Size of each chunk.
+    pub chunk_size: usize,
+}
+
+impl FixedSizeConfig {
+    /// Constructs a new `FixedSizeConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_size` - Fixed size of each chunk.
+    pub fn new(chunk_size: usize) -> Self {
+        Self { chunk_size }
+    }
+
+    /// Constructs a new `FixedSizeConfig`, checking the chunk size.
+    ///
+    /// # Arguments
+    /// * `chunk_size` - Fixed size of each chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chunk_size` is zero.
+    pub fn try_new(chunk_size: usize) -> Result<Self, super::ChunkerError> {
+        FixedSizeChunking::try_new(&[], chunk_size)?;
+        Ok(Self::new(chunk_size))
+    }
+}
+
+impl super::Chunker for FixedSizeConfig {
+    type Iter<'a> = FixedSizeChunking<'a>;
+
+    fn name(&self) -> &'static str {
+        "Fixed Size Chunking"
+    }
+
+    /// Every chunk but the last one has exactly `chunk_size` bytes.
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        super::ChunkSizeParms {
+            min_chunk_size: self.chunk_size,
+            avg_chunk_size: self.chunk_size,
+            max_chunk_size: self.chunk_size,
         }
     }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        FixedSizeChunking::new(source, self.chunk_size)
+    }
 }
 
 impl<'a> Iterator for FixedSizeChunking<'a> {
@@ -41,13 +114,9 @@ impl<'a> Iterator for FixedSizeChunking<'a> {
         if self.current_index >= self.source.len() {
             None
         } else {
-            let length = if self.source.len() - self.current_index < self.chunk_size {
-                self.source.len() - self.current_index
-            } else {
-                self.chunk_size
-            };
             let offset = self.current_index;
-            self.current_index += self.chunk_size;
+            let length = self.find_cut(&self.source[offset..]);
+            self.current_index += length;
 
             Some(super::Chunk { offset, length })
         }
@@ -78,6 +147,20 @@ mod tests {
         assert_eq!(chunks[3].length, 1);
     }
 
+    #[test]
+    fn test_config_matches_constructor() {
+        use crate::Chunker;
+
+        let data = generate_test_data(100);
+        let config = FixedSizeConfig::new(7);
+
+        let expected: Vec<_> = FixedSizeChunking::new(&data, 7).collect();
+        let chunks: Vec<_> = config.chunks(&data).collect();
+
+        assert_eq!(chunks, expected);
+        assert_eq!(config.chunk_parms().max_chunk_size, 7);
+    }
+
     #[test]
     #[should_panic(expected = "Chunk size must be greater than zero")]
     fn test_zero_chunk_size() {
@@ -85,6 +168,17 @@ mod tests {
         let _chunker = FixedSizeChunking::new(&data, 0);
     }
 
+    #[test]
+    fn test_try_new() {
+        let data = generate_test_data(10);
+        assert!(FixedSizeChunking::try_new(&data, 3).is_ok());
+        assert_eq!(
+            FixedSizeChunking::try_new(&data, 0).err(),
+            Some(crate::ChunkerError::ZeroChunkSize)
+        );
+        assert_eq!(FixedSizeConfig::try_new(3), Ok(FixedSizeConfig::new(3)));
+    }
+
     /// Helper function to generate test data.
     fn generate_test_data(length: usize) -> Vec<u8> {
         (0..length).map(|i| i as u8).collect()
@@ -0,0 +1,383 @@
+/// Gear table, where each byte value is mapped to a pseudo-random 31-bit number.
+///
+/// The values are the same as the ones used by the `fastcdc::ronomon` implementation, which allows
+/// checking this implementation for boundary parity against it. The high bit of each value is
+/// cleared, so the sum of two shifted values never overflows.
+#[rustfmt::skip]
+pub const GEAR_TABLE: [u32; 256] = [
+    0x5c95c078, 0x22408989, 0x2d48a214, 0x12842087, 0x530f8afb, 0x474536b9, 0x2963b4f1, 0x44cb738b,
+    0x4ea7403d, 0x4d606b6e, 0x074ec5d3, 0x3af39d18, 0x726003ca, 0x37a62a74, 0x51a2f58e, 0x7506358e,
+    0x5d4ab128, 0x4d4ae17b, 0x41e85924, 0x470c36f7, 0x4741cbe1, 0x01bb7f30, 0x617c1de3, 0x2b0c3a1f,
+    0x50c48f73, 0x21a82d37, 0x6095ace0, 0x419167a0, 0x3caf49b0, 0x40cea62d, 0x66bc1c66, 0x545e1dad,
+    0x2bfa77cd, 0x6e85da24, 0x5fb0bdc5, 0x652cfc29, 0x3a0ae1ab, 0x2837e0f3, 0x6387b70e, 0x13176012,
+    0x4362c2bb, 0x66d8f4b1, 0x37fce834, 0x2c9cd386, 0x21144296, 0x627268a8, 0x650df537, 0x2805d579,
+    0x3b21ebbd, 0x7357ed34, 0x3f58b583, 0x7150ddca, 0x7362225e, 0x620a6070, 0x2c5ef529, 0x7b522466,
+    0x768b78c0, 0x4b54e51e, 0x75fa07e5, 0x06a35fc6, 0x30b71024, 0x1c8626e1, 0x296ad578, 0x28d7be2e,
+    0x1490a05a, 0x7cee43bd, 0x698b56e3, 0x09dc0126, 0x4ed6df6e, 0x02c1bfc7, 0x2a59ad53, 0x29c0e434,
+    0x7d6c5278, 0x507940a7, 0x5ef6ba93, 0x68b6af1e, 0x46537276, 0x611bc766, 0x155c587d, 0x301ba847,
+    0x2cc9dda7, 0x0a438e2c, 0x0a69d514, 0x744c72d3, 0x4f326b9b, 0x7ef34286, 0x4a0ef8a7, 0x6ae06ebe,
+    0x669c5372, 0x12402dcb, 0x5feae99d, 0x76c7f4a7, 0x6abdb79c, 0x0dfaa038, 0x20e2282c, 0x730ed48b,
+    0x069dac2f, 0x168ecf3e, 0x2610e61f, 0x2c512c8e, 0x15fb8c06, 0x5e62bc76, 0x69555135, 0x0adb864c,
+    0x4268f914, 0x349ab3aa, 0x20edfdb2, 0x51727981, 0x37b4b3d8, 0x5dd17522, 0x6b2cbfe4, 0x5c47cf9f,
+    0x30fa1ccd, 0x23dedb56, 0x13d1f50a, 0x64eddee7, 0x0820b0f7, 0x46e07308, 0x1e2d1dfd, 0x17b06c32,
+    0x250036d8, 0x284dbf34, 0x68292ee0, 0x362ec87c, 0x087cb1eb, 0x76b46720, 0x104130db, 0x71966387,
+    0x482dc43f, 0x2388ef25, 0x524144e1, 0x44bd834e, 0x448e7da3, 0x3fa6eaf9, 0x3cda215c, 0x3a500cf3,
+    0x395cb432, 0x5195129f, 0x43945f87, 0x51862ca4, 0x56ea8ff1, 0x201034dc, 0x4d328ff5, 0x7d73a909,
+    0x6234d379, 0x64cfbf9c, 0x36f6589a, 0x0a2ce98a, 0x5fe4d971, 0x03bc15c5, 0x44021d33, 0x16c1932b,
+    0x37503614, 0x1acaf69d, 0x3f03b779, 0x49e61a03, 0x1f52d7ea, 0x1c6ddd5c, 0x062218ce, 0x07e7a11a,
+    0x1905757a, 0x7ce00a53, 0x49f44f29, 0x4bcc70b5, 0x39feea55, 0x5242cee8, 0x3ce56b85, 0x00b81672,
+    0x46beeccc, 0x3ca0ad56, 0x2396cee8, 0x78547f40, 0x6b08089b, 0x66a56751, 0x781e7e46, 0x1e2cf856,
+    0x3bc13591, 0x494a4202, 0x520494d7, 0x2d87459a, 0x757555b6, 0x42284cc1, 0x1f478507, 0x75c95dff,
+    0x35ff8dd7, 0x4e4757ed, 0x2e11f88c, 0x5e1b5048, 0x420e6699, 0x226b0695, 0x4d1679b4, 0x5a22646f,
+    0x161d1131, 0x125c68d9, 0x1313e32e, 0x4aa85724, 0x21dc7ec1, 0x4ffa29fe, 0x72968382, 0x1ca8eef3,
+    0x3f3b1c28, 0x39c2fb6c, 0x6d76493f, 0x7a22a62e, 0x789b1c2a, 0x16e0cb53, 0x7deceeeb, 0x0dc7e1c6,
+    0x5c75bf3d, 0x52218333, 0x106de4d6, 0x7dc64422, 0x65590ff4, 0x2c02ec30, 0x64a9ac67, 0x59cab2e9,
+    0x4a21d2f3, 0x0f616e57, 0x23b54ee8, 0x02730aaa, 0x2f3c634d, 0x7117fc6c, 0x01ac6f05, 0x5a9ed20c,
+    0x158c4e2a, 0x42b699f0, 0x0c7c14b3, 0x02bd9641, 0x15ad56fc, 0x1c722f60, 0x7da1af91, 0x23e0dbcb,
+    0x0e93e12b, 0x64b2791d, 0x440d2476, 0x588ea8dd, 0x4665a658, 0x7446c418, 0x1877a774, 0x5626407e,
+    0x7f63bd46, 0x32d2dbd8, 0x3c790f4a, 0x772b7239, 0x6f8b2826, 0x677ff609, 0x0dc82c11, 0x23ffe354,
+    0x2eac53a6, 0x16139e09, 0x0afd0dbc, 0x2a4d4237, 0x56a368c7, 0x234325e4, 0x2dce9187, 0x32e8ea7e,
+];
+
+/// Gear chunker for data segmentation based on the Gear rolling hash.
+pub struct GearCDC<'a> {
+    /// Gear table used for the hash calculation.
+    table: &'a [u32; 256],
+    /// Mask used to determine chunk cuts before the normal size is reached.
+    mask_s: u32,
+    /// Mask used to determine chunk cuts after the normal size is reached.
+    mask_l: u32,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> GearCDC<'a> {
+    /// Constructs a new `GearCDC` using the default [`GEAR_TABLE`].
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `GearCDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::with_table(source, min_size, avg_size, max_size, &GEAR_TABLE)
+    }
+
+    /// Constructs a new `GearCDC` using the default [`GEAR_TABLE`], checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_table(source, min_size, avg_size, max_size, &GEAR_TABLE)
+    }
+
+    /// Constructs a new `GearCDC` using a custom gear table.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `table` - Gear table mapping each byte value to a 31-bit number.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `GearCDC` instance.
+    pub fn with_table(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        table: &'a [u32; 256],
+    ) -> Self {
+        Self::try_with_table(source, min_size, avg_size, max_size, table)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `GearCDC` using a custom gear table, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    /// * `table` - Gear table mapping each byte value to a 31-bit number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_with_table(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+        table: &'a [u32; 256],
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        let bits = (avg_size as f64).log2().round() as u32;
+
+        Ok(Self {
+            table,
+            mask_s: mask(bits + 1),
+            mask_l: mask(bits - 1),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// The "harder" mask is used until the normal size is reached and the "easier" one afterwards,
+    /// which keeps the chunk sizes close to the average.
+    ///
+    /// Unlike the search of [`FastCDC`](super::FastCDC), this search is scalar only. The hash is
+    /// shifted right, which rounds at every byte, so the hashes of a block of bytes aren't the
+    /// prefix sums the vectorized search computes.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let normal = normal_size(self.chunk_parms.avg_chunk_size, min_size, limit);
+        let data = &data[..limit];
+
+        let mut hash = 0u32;
+        let mut pos = min_size;
+        while pos < normal {
+            hash = (hash >> 1).wrapping_add(self.table[data[pos] as usize]);
+            pos += 1;
+            if hash & self.mask_s == 0 {
+                return pos;
+            }
+        }
+        while pos < limit {
+            hash = (hash >> 1).wrapping_add(self.table[data[pos] as usize]);
+            pos += 1;
+            if hash & self.mask_l == 0 {
+                return pos;
+            }
+        }
+
+        limit
+    }
+}
+
+/// Configuration of the Gear chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GearConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl GearConfig {
+    /// Constructs a new `GearConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `GearConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        GearCDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for GearConfig {
+    type Iter<'a> = GearCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Native Gear Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        GearCDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for GearCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Gear hash.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+/// Returns a bit mask with the `bits` least-significant bits set.
+fn mask(bits: u32) -> u32 {
+    debug_assert!((1..=31).contains(&bits));
+    (1u32 << bits) - 1
+}
+
+/// Finds the chunk length after which the "easier" mask is used, what the FastCDC paper refers
+/// to as the "normal size".
+///
+/// Follows the reference implementation by Joran Dirk Greef, in which the normal size is shifted
+/// down by one and a half of the minimum size. If the result falls below the minimum size, only
+/// the "easier" mask is ever used.
+fn normal_size(avg_size: usize, min_size: usize, limit: usize) -> usize {
+    let offset = (min_size + min_size.div_ceil(2)).min(avg_size);
+    (avg_size - offset).min(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collects the chunks produced by `fastcdc::ronomon` for the same data and parameters.
+    fn ronomon_chunks(data: &[u8], min: usize, avg: usize, max: usize) -> Vec<crate::Chunk> {
+        fastcdc::ronomon::FastCDC::new(data, min, avg, max)
+            .map(|chunk| crate::Chunk {
+                offset: chunk.offset,
+                length: chunk.length,
+            })
+            .collect()
+    }
+
+    /// Tests that the boundaries match the ronomon implementation using the same gear table.
+    #[test]
+    fn test_ronomon_parity() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(0xC0FFEE));
+        let parms = [
+            (64, 256, 1024),
+            (2048, 8192, 65536),
+            (8192, 10240, 65536),
+            (16384, 32768, 65536),
+            (65536, 262_144, 1_048_576),
+        ];
+
+        for (min, avg, max) in parms {
+            let chunks: Vec<_> = GearCDC::new(&data, min, avg, max).collect();
+            assert_eq!(
+                chunks,
+                ronomon_chunks(&data, min, avg, max),
+                "Boundaries differ for min={} avg={} max={}",
+                min,
+                avg,
+                max
+            );
+        }
+    }
+
+    /// Tests that pathological data is cut at the maximum chunk size.
+    #[test]
+    fn test_zero_data() {
+        let data = vec![0u8; 10_000];
+        let chunks: Vec<_> = GearCDC::new(&data, 256, 1024, 4096).collect();
+
+        assert_eq!(chunks, ronomon_chunks(&data, 256, 1024, 4096));
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.length == 4096));
+    }
+
+    /// Tests that a custom gear table changes the boundaries.
+    #[test]
+    fn test_custom_table() {
+        let data = crate::generate_data_block(256 * 1024, Some(1));
+        let mut table = GEAR_TABLE;
+        table.reverse();
+
+        let default: Vec<_> = GearCDC::new(&data, 256, 1024, 4096).collect();
+        let custom: Vec<_> = GearCDC::with_table(&data, 256, 1024, 4096, &table).collect();
+
+        assert_ne!(default, custom);
+    }
+
+    /// Tests GearCDC with very small data to check edge cases.
+    #[test]
+    fn test_small_data() {
+        let data = crate::generate_data_block(50, Some(1));
+        let chunks: Vec<_> = GearCDC::new(&data, 64, 256, 1024).collect();
+
+        assert_eq!(chunks.len(), 1, "Should create exactly one chunk");
+        assert_eq!(chunks[0].length, 50, "The chunk should contain all data");
+        assert_eq!(GearCDC::new(&[], 64, 256, 1024).count(), 0);
+    }
+
+    /// Tests GearCDC with a minimum chunk size that is too small.
+    #[test]
+    #[should_panic(expected = "Min chunk size out of valid range")]
+    fn test_min_size_too_small() {
+        let _chunker = GearCDC::new(&[], 32, 256, 1024);
+    }
+
+    /// Tests GearCDC with a maximum chunk size that is too large.
+    #[test]
+    #[should_panic(expected = "Max chunk size out of valid range")]
+    fn test_max_size_too_large() {
+        let _chunker = GearCDC::new(&[], 64, 256, 32 * 1024 * 1024);
+    }
+}
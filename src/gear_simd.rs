@@ -0,0 +1,336 @@
+//! Vectorized cut-point search of the Gear rolling hash.
+//!
+//! The Gear hash after a byte is the hash after the previous byte shifted left by one bit plus
+//! the table value of the byte. For a block of consecutive bytes, the hashes after each of them
+//! are thus the prefix sums of the table values, each shifted by its distance to the end of the
+//! prefix, plus the hash before the block shifted by the position within the block. The prefix
+//! sums are computed with a logarithmic number of lane shifts, so a whole block of hashes is
+//! checked against the mask at once, giving the same cut points as the scalar search.
+
+use super::fast_cdc::GEAR;
+
+/// Signature of a cut-point search.
+///
+/// Returns the first position in `start..end` where the hash, starting from zero at `start`, has
+/// none of the bits of the mask set. The mask is `mask_s` before `center` and `mask_l` after it.
+pub(crate) type FindCut = fn(&[u8], usize, usize, usize, u64, u64) -> Option<usize>;
+
+/// Returns the fastest vectorized cut-point search supported by the CPU, if any.
+pub(crate) fn vector_find_cut() -> Option<FindCut> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") {
+            return Some(x86::find_cut_avx512);
+        }
+        if is_x86_feature_detected!("avx2") {
+            return Some(x86::find_cut_avx2);
+        }
+    }
+
+    None
+}
+
+/// Returns every vectorized cut-point search supported by the CPU along with its name, from the
+/// slowest to the fastest.
+#[cfg(test)]
+pub(crate) fn supported_searches() -> Vec<(&'static str, FindCut)> {
+    #[allow(unused_mut)]
+    let mut searches: Vec<(&'static str, FindCut)> = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") {
+            searches.push(("avx2", x86::find_cut_avx2));
+        }
+        if is_x86_feature_detected!("avx512f") {
+            searches.push(("avx512f", x86::find_cut_avx512));
+        }
+    }
+
+    searches
+}
+
+/// Reports the searches a test ran and checks that they include the ones listed in the
+/// `REQUIRE_VECTOR_SEARCHES` environment variable, like `avx2,avx512f`, so a runner missing a
+/// CPU feature fails instead of skipping its search.
+#[cfg(test)]
+pub(crate) fn check_tested_searches(test: &str, searches: &[(&'static str, FindCut)]) {
+    let names: Vec<_> = searches.iter().map(|(name, _)| *name).collect();
+    println!("{} tested the vectorized searches {:?}", test, names);
+
+    // The dispatch picks the fastest supported search.
+    assert_eq!(
+        vector_find_cut().map(|search| search as usize),
+        searches.last().map(|&(_, search)| search as usize)
+    );
+
+    let required = std::env::var("REQUIRE_VECTOR_SEARCHES").unwrap_or_default();
+    for name in required.split(',').filter(|name| !name.is_empty()) {
+        assert!(
+            names.contains(&name),
+            "{} didn't test the required search {}",
+            test,
+            name
+        );
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod x86 {
+    use std::arch::x86_64::*;
+
+    use super::GEAR;
+
+    /// Cut-point search processing 8 bytes at a time with AVX2.
+    pub(crate) fn find_cut_avx2(
+        data: &[u8],
+        start: usize,
+        center: usize,
+        end: usize,
+        mask_s: u64,
+        mask_l: u64,
+    ) -> Option<usize> {
+        // SAFETY: Only handed out after the CPU was detected to support AVX2.
+        unsafe { search_avx2(data, start, center, end, mask_s, mask_l) }
+    }
+
+    /// Cut-point search processing 16 bytes at a time with AVX-512.
+    pub(crate) fn find_cut_avx512(
+        data: &[u8],
+        start: usize,
+        center: usize,
+        end: usize,
+        mask_s: u64,
+        mask_l: u64,
+    ) -> Option<usize> {
+        // SAFETY: Only handed out after the CPU was detected to support AVX-512F.
+        unsafe { search_avx512(data, start, center, end, mask_s, mask_l) }
+    }
+
+    /// Computes the prefix sums of a block of 4 table values.
+    ///
+    /// Lane i receives lane i - 1 shifted by one bit, then lane i - 2 shifted by two bits.
+    #[target_feature(enable = "avx2")]
+    fn prefix_avx2(sums: __m256i) -> __m256i {
+        let zero = _mm256_setzero_si256();
+        let prev = _mm256_permute4x64_epi64::<0b10_01_00_00>(sums);
+        let prev = _mm256_blend_epi32::<0b0000_0011>(prev, zero);
+        let sums = _mm256_add_epi64(sums, _mm256_slli_epi64::<1>(prev));
+        let prev = _mm256_permute4x64_epi64::<0b01_00_00_00>(sums);
+        let prev = _mm256_blend_epi32::<0b0000_1111>(prev, zero);
+        _mm256_add_epi64(sums, _mm256_slli_epi64::<2>(prev))
+    }
+
+    /// Looks up the table values of 4 bytes.
+    #[target_feature(enable = "avx2")]
+    unsafe fn gather_avx2(bytes: __m128i) -> __m256i {
+        let indices = _mm256_cvtepu8_epi64(bytes);
+        _mm256_i64gather_epi64::<8>(GEAR.as_ptr() as *const i64, indices)
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn search_avx2(
+        data: &[u8],
+        start: usize,
+        center: usize,
+        end: usize,
+        mask_s: u64,
+        mask_l: u64,
+    ) -> Option<usize> {
+        let zero = _mm256_setzero_si256();
+        let shifts_low = _mm256_setr_epi64x(1, 2, 3, 4);
+        let shifts_high = _mm256_setr_epi64x(5, 6, 7, 8);
+        let mut pos = start;
+        // Hash before the current block, broadcast to all lanes.
+        let mut carry = zero;
+
+        for (stop, mask) in [(center.clamp(start, end), mask_s), (end, mask_l)] {
+            let mask_v = _mm256_set1_epi64x(mask as i64);
+
+            // Two blocks of 4 bytes per iteration, so only the carry of the second block depends
+            // on the previous iteration.
+            while pos + 8 <= stop {
+                let bytes = u64::from_le_bytes(data[pos..pos + 8].try_into().unwrap());
+                let bytes = _mm_cvtsi64_si128(bytes as i64);
+                let low = prefix_avx2(gather_avx2(bytes));
+                let high = prefix_avx2(gather_avx2(_mm_srli_si128::<4>(bytes)));
+
+                let low_last = _mm256_permute4x64_epi64::<0b11_11_11_11>(low);
+                let low = _mm256_add_epi64(low, _mm256_sllv_epi64(carry, shifts_low));
+                let high = _mm256_add_epi64(high, _mm256_sllv_epi64(low_last, shifts_low));
+                let high = _mm256_add_epi64(high, _mm256_sllv_epi64(carry, shifts_high));
+
+                let hits_low = _mm256_cmpeq_epi64(_mm256_and_si256(low, mask_v), zero);
+                let hits_high = _mm256_cmpeq_epi64(_mm256_and_si256(high, mask_v), zero);
+                let hits = _mm256_movemask_pd(_mm256_castsi256_pd(hits_low))
+                    | _mm256_movemask_pd(_mm256_castsi256_pd(hits_high)) << 4;
+                if hits != 0 {
+                    return Some(pos + hits.trailing_zeros() as usize);
+                }
+
+                carry = _mm256_permute4x64_epi64::<0b11_11_11_11>(high);
+                pos += 8;
+            }
+
+            let mut hash = _mm256_extract_epi64::<0>(carry) as u64;
+            while pos < stop {
+                hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+                if hash & mask == 0 {
+                    return Some(pos);
+                }
+                pos += 1;
+            }
+            carry = _mm256_set1_epi64x(hash as i64);
+        }
+
+        None
+    }
+
+    /// Computes the prefix sums of a block of 8 table values.
+    ///
+    /// Lane i receives lane i - k shifted by k bits, for k = 1, 2, 4.
+    #[target_feature(enable = "avx512f")]
+    fn prefix_avx512(sums: __m512i) -> __m512i {
+        let zero = _mm512_setzero_si512();
+        let prev = _mm512_alignr_epi64::<7>(sums, zero);
+        let sums = _mm512_add_epi64(sums, _mm512_slli_epi64::<1>(prev));
+        let prev = _mm512_alignr_epi64::<6>(sums, zero);
+        let sums = _mm512_add_epi64(sums, _mm512_slli_epi64::<2>(prev));
+        let prev = _mm512_alignr_epi64::<4>(sums, zero);
+        _mm512_add_epi64(sums, _mm512_slli_epi64::<4>(prev))
+    }
+
+    /// Looks up the table values of 8 bytes.
+    #[target_feature(enable = "avx512f")]
+    unsafe fn gather_avx512(bytes: __m128i) -> __m512i {
+        let indices = _mm512_cvtepu8_epi64(bytes);
+        _mm512_i64gather_epi64::<8>(indices, GEAR.as_ptr() as *const i64)
+    }
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn search_avx512(
+        data: &[u8],
+        start: usize,
+        center: usize,
+        end: usize,
+        mask_s: u64,
+        mask_l: u64,
+    ) -> Option<usize> {
+        let last_lane = _mm512_set1_epi64(7);
+        let shifts_low = _mm512_setr_epi64(1, 2, 3, 4, 5, 6, 7, 8);
+        let shifts_high = _mm512_setr_epi64(9, 10, 11, 12, 13, 14, 15, 16);
+        let mut pos = start;
+        // Hash before the current block, broadcast to all lanes.
+        let mut carry = _mm512_setzero_si512();
+
+        for (stop, mask) in [(center.clamp(start, end), mask_s), (end, mask_l)] {
+            let mask_v = _mm512_set1_epi64(mask as i64);
+
+            // Two blocks of 8 bytes per iteration, so only the carry of the second block depends
+            // on the previous iteration.
+            while pos + 16 <= stop {
+                let bytes = _mm_loadu_si128(data[pos..pos + 16].as_ptr() as *const __m128i);
+                let low = prefix_avx512(gather_avx512(bytes));
+                let high = prefix_avx512(gather_avx512(_mm_srli_si128::<8>(bytes)));
+
+                let low_last = _mm512_permutexvar_epi64(last_lane, low);
+                let low = _mm512_add_epi64(low, _mm512_sllv_epi64(carry, shifts_low));
+                let high = _mm512_add_epi64(high, _mm512_sllv_epi64(low_last, shifts_low));
+                let high = _mm512_add_epi64(high, _mm512_sllv_epi64(carry, shifts_high));
+
+                let hits = u32::from(_mm512_testn_epi64_mask(low, mask_v))
+                    | u32::from(_mm512_testn_epi64_mask(high, mask_v)) << 8;
+                if hits != 0 {
+                    return Some(pos + hits.trailing_zeros() as usize);
+                }
+
+                carry = _mm512_permutexvar_epi64(last_lane, high);
+                pos += 16;
+            }
+
+            let mut hash = _mm_cvtsi128_si64(_mm512_castsi512_si128(carry)) as u64;
+            while pos < stop {
+                hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+                if hash & mask == 0 {
+                    return Some(pos);
+                }
+                pos += 1;
+            }
+            carry = _mm512_set1_epi64(hash as i64);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scalar cut-point search, rolling one byte at a time.
+    fn find_cut_scalar(
+        data: &[u8],
+        start: usize,
+        center: usize,
+        end: usize,
+        mask_s: u64,
+        mask_l: u64,
+    ) -> Option<usize> {
+        let mut hash = 0u64;
+        (start..end).find(|&pos| {
+            hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+            let mask = if pos < center { mask_s } else { mask_l };
+            hash & mask == 0
+        })
+    }
+
+    /// Searches with mask and range combinations covering both phases and the scalar tails.
+    fn assert_matches_scalar(find_cut: FindCut) {
+        let data = crate::generate_data_block(64 * 1024, Some(101));
+        let zeros = vec![0u8; 4096];
+
+        for (mask_s, mask_l) in [(0x0000_d903_0353_0000, 0x0000_d901_0353_0000), (0x3, 0x1)] {
+            for (start, center, end) in [
+                (0, 0, 4096),
+                (3, 1000, 4093),
+                (1000, 999, 65_536),
+                (17, 64_000, 65_535),
+                (100, 100, 107),
+            ] {
+                assert_eq!(
+                    find_cut(&data, start, center, end, mask_s, mask_l),
+                    find_cut_scalar(&data, start, center, end, mask_s, mask_l),
+                    "Range {}..{} center {}",
+                    start,
+                    end,
+                    center
+                );
+            }
+            assert_eq!(
+                find_cut(&zeros, 5, 2000, 4000, mask_s, mask_l),
+                find_cut_scalar(&zeros, 5, 2000, 4000, mask_s, mask_l)
+            );
+        }
+
+        // All cut points of random data, with masks matching often.
+        let (mask_s, mask_l) = (0xff00_0000_0000_0000, 0xf000_0000_0000_0000);
+        let mut start = 0;
+        while let Some(pos) = find_cut_scalar(&data, start, start + 300, data.len(), mask_s, mask_l)
+        {
+            assert_eq!(
+                find_cut(&data, start, start + 300, data.len(), mask_s, mask_l),
+                Some(pos)
+            );
+            start = pos + 1;
+        }
+    }
+
+    /// Tests every search supported by the CPU against the scalar one.
+    #[test]
+    fn test_vector_matches_scalar() {
+        let searches = supported_searches();
+        for &(_, search) in &searches {
+            assert_matches_scalar(search);
+        }
+        check_tested_searches("test_vector_matches_scalar", &searches);
+    }
+}
@@ -0,0 +1,257 @@
+use super::stream::StreamChunk;
+use super::Chunker;
+
+/// Push-based chunker fed with fragments of a stream of any size.
+///
+/// Only the data following the last emitted boundary is kept between fragments, which is at most
+/// the maximum chunk size plus the lookahead of the chunker, while the boundaries are the same as
+/// chunking the whole stream at once, however the stream is split.
+///
+/// The fragments are collected until they hold more than the maximum chunk size plus the
+/// lookahead, which guarantees a final boundary, before the chunker searches them. Every byte is
+/// thus searched about twice at most, even if the fragments are tiny, at the cost of reporting a
+/// boundary up to that many bytes after the data of the chunk was fed.
+pub struct IncrementalChunker<C> {
+    /// Chunking algorithm configuration.
+    chunker: C,
+    /// Data of the stream following the last emitted boundary.
+    buffer: Vec<u8>,
+    /// Stream offset of the first byte in the buffer.
+    offset: u64,
+    /// Amount of data which always contains a final boundary.
+    window_size: usize,
+}
+
+impl<C: Chunker> IncrementalChunker<C> {
+    /// Constructs a new `IncrementalChunker`.
+    ///
+    /// # Arguments
+    /// * `chunker` - Chunking algorithm configuration.
+    ///
+    /// # Returns
+    ///
+    /// A new `IncrementalChunker` instance.
+    pub fn new(chunker: C) -> Self {
+        let window_size = chunker.chunk_parms().max_chunk_size + chunker.lookahead() + 1;
+
+        Self {
+            chunker,
+            buffer: Vec::new(),
+            offset: 0,
+            window_size,
+        }
+    }
+
+    /// Returns the stream offset of the next chunk.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the number of bytes fed but not yet part of an emitted chunk.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Feeds the next fragment of the stream.
+    ///
+    /// # Arguments
+    /// * `data` - Fragment following the previously fed data.
+    ///
+    /// # Returns
+    ///
+    /// The chunks completed by the fragment.
+    pub fn update(&mut self, data: &[u8]) -> Vec<StreamChunk> {
+        let mut chunks = Vec::new();
+        self.update_with(data, |chunk, _| chunks.push(chunk));
+        chunks
+    }
+
+    /// Feeds the next fragment of the stream and passes the completed chunks along with their
+    /// bytes to `f`.
+    ///
+    /// # Arguments
+    /// * `data` - Fragment following the previously fed data.
+    /// * `f` - Function called with every completed chunk and its bytes, in stream order.
+    pub fn update_with(&mut self, data: &[u8], f: impl FnMut(StreamChunk, &[u8])) {
+        if self.buffer.is_empty() {
+            // The fragment is searched in place, only its unfinished part is copied.
+            let end = self.emit(data, false, f);
+            self.buffer.extend_from_slice(&data[end..]);
+        } else {
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.extend_from_slice(data);
+            let end = self.emit(&buffer, false, f);
+            buffer.drain(..end);
+            self.buffer = buffer;
+        }
+    }
+
+    /// Ends the stream.
+    ///
+    /// # Returns
+    ///
+    /// The chunks of the data fed after the last completed chunk.
+    pub fn finish(self) -> Vec<StreamChunk> {
+        let mut chunks = Vec::new();
+        self.finish_with(|chunk, _| chunks.push(chunk));
+        chunks
+    }
+
+    /// Ends the stream and passes the remaining chunks along with their bytes to `f`.
+    ///
+    /// # Arguments
+    /// * `f` - Function called with every remaining chunk and its bytes, in stream order.
+    pub fn finish_with(mut self, f: impl FnMut(StreamChunk, &[u8])) {
+        let buffer = std::mem::take(&mut self.buffer);
+        self.emit(&buffer, true, f);
+    }
+
+    /// Emits the final chunks of `data`, which starts at the stream offset of the next chunk.
+    ///
+    /// Unless the stream ends with `data`, it isn't searched before it holds a full window, and
+    /// chunks ending within the lookahead before its end aren't final.
+    ///
+    /// # Returns
+    ///
+    /// The position in `data` following the last emitted chunk.
+    fn emit(&mut self, data: &[u8], eof: bool, mut f: impl FnMut(StreamChunk, &[u8])) -> usize {
+        if !eof && data.len() < self.window_size {
+            return 0;
+        }
+
+        let lookahead = self.chunker.lookahead();
+        let mut end = 0;
+        for chunk in self
+            .chunker
+            .chunks(data)
+            .take_while(|chunk| eof || chunk.offset + chunk.length + lookahead < data.len())
+        {
+            end = chunk.offset + chunk.length;
+            f(
+                StreamChunk {
+                    offset: self.offset + chunk.offset as u64,
+                    length: chunk.length,
+                },
+                &data[chunk.offset..end],
+            );
+        }
+        self.offset += end as u64;
+        end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        ChunkSizeParms, FastCDCConfig, FixedSizeConfig, GearConfig, MaxpConfig, Normalization,
+        RabinConfig,
+    };
+
+    /// Chunk size parameters used by the incremental tests.
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 1024,
+        avg_chunk_size: 4096,
+        max_chunk_size: 16384,
+    };
+
+    /// Feeds the data in fragments of random sizes, from single bytes to several chunks, and
+    /// checks that the chunks and their bytes are the same as chunking the data in memory.
+    fn assert_random_splits_match<C: Chunker + Clone>(chunker: C, data: &[u8], seed: u64) {
+        let expected: Vec<_> = chunker
+            .chunks(data)
+            .map(|chunk| StreamChunk {
+                offset: chunk.offset as u64,
+                length: chunk.length,
+            })
+            .collect();
+
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut incremental = IncrementalChunker::new(chunker.clone());
+        let mut chunks = Vec::new();
+        let mut check = |chunk: StreamChunk, bytes: &[u8]| {
+            let start = chunk.offset as usize;
+            assert_eq!(bytes, &data[start..start + chunk.length]);
+            chunks.push(chunk);
+        };
+        let mut rest = data;
+        while !rest.is_empty() {
+            let max_len =
+                [1, 100, PARMS.max_chunk_size, 4 * PARMS.max_chunk_size][rng.gen_range(0..4)];
+            let (fragment, tail) = rest.split_at(rng.gen_range(0..=max_len).min(rest.len()));
+            incremental.update_with(fragment, &mut check);
+            assert!(incremental.buffered() <= PARMS.max_chunk_size + chunker.lookahead());
+            rest = tail;
+        }
+        incremental.finish_with(&mut check);
+
+        assert_eq!(chunks, expected, "{} with seed {}", chunker.name(), seed);
+    }
+
+    /// Tests that randomly split data gives the in-memory boundaries for various chunkers.
+    #[test]
+    fn test_random_splits() {
+        let data = crate::generate_data_block(512 * 1024 + 77, Some(21));
+
+        for seed in 0..8 {
+            assert_random_splits_match(FixedSizeConfig::new(5000), &data, seed);
+            assert_random_splits_match(GearConfig::new(PARMS), &data, seed);
+            assert_random_splits_match(
+                FastCDCConfig::new(PARMS, Normalization::Level2),
+                &data,
+                seed,
+            );
+            assert_random_splits_match(RabinConfig::new(64, PARMS), &data, seed);
+            assert_random_splits_match(MaxpConfig::new(PARMS), &data, seed);
+        }
+    }
+
+    /// Tests that the chunks returned by `update` and `finish` cover the stream in order.
+    #[test]
+    fn test_update_and_finish() {
+        let data = crate::generate_data_block(100 * 1024, Some(22));
+        let chunker = GearConfig::new(PARMS);
+
+        let mut incremental = IncrementalChunker::new(chunker);
+        let mut chunks = Vec::new();
+        let mut fed = 0;
+        for fragment in data.chunks(3000) {
+            chunks.extend(incremental.update(fragment));
+            fed += fragment.len() as u64;
+            let emitted: u64 = chunks.iter().map(|chunk| chunk.length as u64).sum();
+            assert_eq!(incremental.offset(), emitted);
+            assert_eq!(incremental.offset() + incremental.buffered() as u64, fed);
+        }
+        chunks.extend(incremental.finish());
+
+        let expected: Vec<_> = chunker
+            .chunks(&data)
+            .map(|chunk| StreamChunk {
+                offset: chunk.offset as u64,
+                length: chunk.length,
+            })
+            .collect();
+        assert_eq!(chunks, expected);
+    }
+
+    /// Tests an empty stream and a stream shorter than the minimum chunk size.
+    #[test]
+    fn test_short_streams() {
+        let incremental = IncrementalChunker::new(GearConfig::new(PARMS));
+        assert!(incremental.finish().is_empty());
+
+        let mut incremental = IncrementalChunker::new(GearConfig::new(PARMS));
+        assert!(incremental.update(&[]).is_empty());
+        assert!(incremental.update(&[1, 2, 3]).is_empty());
+        assert_eq!(
+            incremental.finish(),
+            vec![StreamChunk {
+                offset: 0,
+                length: 3
+            }]
+        );
+    }
+}
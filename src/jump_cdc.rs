@@ -0,0 +1,273 @@
+use super::fast_cdc::GEAR;
+
+/// Jump-ahead chunker for data segmentation based on the Gear rolling hash, in the style of
+/// QuickCDC and jump-based chunking.
+///
+/// Besides the cut condition, an easier jump condition is checked at every position. When it
+/// holds without the cut condition, the chunker skips the jump length of bytes without hashing
+/// them and restarts the hash afterwards. On average one jump happens per chunk, so the expected
+/// chunk length is the minimum size plus the jump length plus the distance given by the cut mask,
+/// and the skipped bytes aren't hashed at all, at the expense of some deduplication.
+pub struct JumpCDC<'a> {
+    /// Mask used to determine chunk cuts.
+    cut_mask: u64,
+    /// Mask used to determine jumps, with one bit less than the cut mask.
+    jump_mask: u64,
+    /// Number of bytes skipped by a jump.
+    jump_len: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> JumpCDC<'a> {
+    /// Constructs a new `JumpCDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `jump_len` - Number of bytes skipped by a jump.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated, or if the jump length is not smaller
+    /// than the difference between the average and the minimum chunk size.
+    ///
+    /// # Returns
+    ///
+    /// A new `JumpCDC` instance.
+    pub fn new(
+        source: &'a [u8],
+        jump_len: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_new(source, jump_len, min_size, avg_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `JumpCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `jump_len` - Number of bytes skipped by a jump.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated or if the jump length doesn't
+    /// fit between the minimum and average chunk sizes.
+    pub fn try_new(
+        source: &'a [u8],
+        jump_len: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+        if jump_len == 0 || jump_len >= avg_size.saturating_sub(min_size) {
+            return Err(super::ChunkerError::JumpLength(jump_len));
+        }
+
+        // Expected chunk length is the minimum size plus the jump length plus 2^bits.
+        let target = avg_size - min_size - jump_len;
+        let bits = ((target as f64).log2().round() as u32).max(1);
+
+        Ok(Self {
+            cut_mask: !(u64::MAX >> bits),
+            jump_mask: !(u64::MAX >> (bits - 1)),
+            jump_len,
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+
+        let mut hash = 0u64;
+        let mut pos = min_size;
+        while pos < limit {
+            hash = (hash << 1).wrapping_add(GEAR[data[pos] as usize]);
+            if hash & self.cut_mask == 0 {
+                return pos + 1;
+            }
+            if hash & self.jump_mask == 0 {
+                pos += self.jump_len;
+                hash = 0;
+            }
+            pos += 1;
+        }
+
+        limit
+    }
+}
+
+/// Configuration of the jump-ahead chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct JumpConfig {
+    /// Number of bytes skipped by a jump.
+    pub jump_len: usize,
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl JumpConfig {
+    /// Constructs a new `JumpConfig`.
+    ///
+    /// # Arguments
+    /// * `jump_len` - Number of bytes skipped by a jump.
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(jump_len: usize, chunk_parms: super::ChunkSizeParms) -> Self {
+        Self {
+            jump_len,
+            chunk_parms,
+        }
+    }
+
+    /// Constructs a new `JumpConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `jump_len` - Number of bytes skipped by a jump.
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated or if the jump length doesn't
+    /// fit between the minimum and average chunk sizes.
+    pub fn try_new(
+        jump_len: usize,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        JumpCDC::try_new(
+            &[],
+            jump_len,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(jump_len, chunk_parms))
+    }
+}
+
+impl super::Chunker for JumpConfig {
+    type Iter<'a> = JumpCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Jump Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        JumpCDC::new(
+            source,
+            self.jump_len,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for JumpCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Gear rolling hash, jumping ahead when possible.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests chunk invariants and the average chunk size on random data.
+    #[test]
+    fn test_random_average() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(91));
+
+        for jump_len in [1024, 4096, 6144] {
+            let chunks: Vec<_> = JumpCDC::new(&data, jump_len, 2048, 10240, 65536).collect();
+
+            let mut offset = 0;
+            for (i, chunk) in chunks.iter().enumerate() {
+                assert_eq!(chunk.offset, offset);
+                assert!(chunk.length <= 65536);
+                assert!(chunk.length >= 2048 || i + 1 == chunks.len());
+                offset += chunk.length;
+            }
+            assert_eq!(offset, data.len());
+
+            let average = data.len() / chunks.len();
+            assert!(
+                (8192..=12288).contains(&average),
+                "Jump {}, average {}",
+                jump_len,
+                average
+            );
+        }
+    }
+
+    /// Tests that the chunks following an insertion are found again.
+    #[test]
+    fn test_resynchronizes_after_insert() {
+        let original = crate::generate_data_block(1024 * 1024, Some(92));
+        let mut edited = original.clone();
+        edited.insert(5000, 0x5a);
+
+        let lengths = |data: &[u8]| -> Vec<usize> {
+            JumpCDC::new(data, 2048, 1024, 8192, 32768)
+                .map(|chunk| chunk.length)
+                .collect()
+        };
+        let (original_lengths, edited_lengths) = (lengths(&original), lengths(&edited));
+        assert_ne!(original_lengths, edited_lengths);
+        assert_eq!(
+            original_lengths[original_lengths.len() - 50..],
+            edited_lengths[edited_lengths.len() - 50..]
+        );
+    }
+
+    /// Tests JumpCDC with a jump length exceeding the average chunk size.
+    #[test]
+    #[should_panic(expected = "Jump length out of valid range")]
+    fn test_jump_length_too_large() {
+        let _chunker = JumpCDC::new(&[], 4096, 1024, 4096, 16384);
+    }
+}
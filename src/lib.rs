@@ -1,11 +1,59 @@
+use std::fmt::{Display, Formatter};
+
 use rand::{random, RngCore, rngs::SmallRng, SeedableRng};
 use zerocopy::AsBytes;
 
-pub use fsc::FixedSizeChunking;
-pub use rabin_cdc::RabinCDC;
+pub use adapters::{RonomonConfig, V2020Config};
+pub use ae_cdc::{AECDC, AEConfig};
+pub use async_stream::AsyncStreamChunker;
+pub use borg_cdc::{BORG_DEFAULT_PARAMS, BorgCDC, BorgConfig, BorgParams};
+pub use buzhash_cdc::{buzhash_table, BuzhashCDC, BuzhashConfig, DEFAULT_BUZHASH_SEED};
+pub use caibx::{CA_FORMAT_SHA512_256, CaibxEntry, CaibxIndex, chunk_id, ChunkId};
+pub use casync_cdc::{
+    CASYNC_BUZHASH_TABLE, casync_discriminator, CASYNC_DEFAULT_PARMS, CasyncCDC, CasyncConfig,
+};
+pub use fast_cdc::{FastCDC, FastCDCConfig, FastCDCVersion, Normalization};
+pub use fsc::{FixedSizeChunking, FixedSizeConfig};
+pub use gear_cdc::{GEAR_TABLE, GearCDC, GearConfig};
+pub use incremental::IncrementalChunker;
+pub use jump_cdc::{JumpCDC, JumpConfig};
+pub use maxp_cdc::{MaxpCDC, MaxpConfig};
+pub use parallel::chunk_parallel;
+pub use rabin_cdc::{
+    check_polynomial, FP_POLY, is_irreducible, RabinCDC, RabinConfig, RabinTables,
+    random_polynomial,
+};
+pub use ram_cdc::{RamCDC, RamConfig};
+pub use restic_cdc::{
+    ResticCDC, ResticConfig, RESTIC_AVERAGE_BITS, RESTIC_MAX_SIZE, RESTIC_MIN_SIZE,
+};
+pub use rsync_cdc::{RsyncCDC, RsyncConfig};
+pub use stream::{
+    ChunkData, DEFAULT_BUFFER_SIZE, StreamChunk, StreamChunker, StreamChunkerWithData,
+};
+pub use tttd_cdc::{TttdCDC, TttdConfig};
 
+mod adapters;
+mod ae_cdc;
+mod async_stream;
+mod borg_cdc;
+mod buzhash_cdc;
+mod caibx;
+mod casync_cdc;
+mod fast_cdc;
 mod fsc;
+mod gear_cdc;
+mod gear_simd;
+mod incremental;
+mod jump_cdc;
+mod maxp_cdc;
+mod parallel;
 mod rabin_cdc;
+mod ram_cdc;
+mod restic_cdc;
+mod rsync_cdc;
+mod stream;
+mod tttd_cdc;
 
 /// Smallest acceptable value for the minimum chunk size.
 const MIN_MIN_CHUNK_SIZE: usize = 64;
@@ -26,7 +74,7 @@ const MIN_MAX_CHUNK_SIZE: usize = 1024;
 const MAX_MAX_CHUNK_SIZE: usize = 16_777_216;
 
 /// Represents parameters for determining chunk sizes.
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct ChunkSizeParms {
     /// Minimum expected chunk size.
     pub min_chunk_size: usize,
@@ -36,6 +84,155 @@ pub struct ChunkSizeParms {
     pub max_chunk_size: usize,
 }
 
+impl ChunkSizeParms {
+    /// Constructs new chunk size parameters, checking the sizes.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_chunk_size` - Minimum expected chunk size.
+    /// * `avg_chunk_size` - Average expected chunk size.
+    /// * `max_chunk_size` - Maximum expected chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the sizes is out of its valid range, or if the sizes don't
+    /// satisfy `min <= avg <= max`.
+    pub fn new(
+        min_chunk_size: usize,
+        avg_chunk_size: usize,
+        max_chunk_size: usize,
+    ) -> Result<Self, ChunkerError> {
+        let parms = Self {
+            min_chunk_size: Self::check_min_chunk_size(min_chunk_size)?,
+            avg_chunk_size: Self::check_avg_chunk_size(avg_chunk_size)?,
+            max_chunk_size: Self::check_max_chunk_size(max_chunk_size)?,
+        };
+
+        if min_chunk_size > avg_chunk_size || avg_chunk_size > max_chunk_size {
+            return Err(ChunkerError::UnorderedChunkSizes(parms));
+        }
+        Ok(parms)
+    }
+
+    /// Checks that a minimum chunk size is within the valid range.
+    ///
+    /// # Returns
+    ///
+    /// The size if it is valid, otherwise [`ChunkerError::MinChunkSize`].
+    pub fn check_min_chunk_size(size: usize) -> Result<usize, ChunkerError> {
+        match size {
+            MIN_MIN_CHUNK_SIZE..=MAX_MIN_CHUNK_SIZE => Ok(size),
+            _ => Err(ChunkerError::MinChunkSize(size)),
+        }
+    }
+
+    /// Checks that an average chunk size is within the valid range.
+    ///
+    /// # Returns
+    ///
+    /// The size if it is valid, otherwise [`ChunkerError::AvgChunkSize`].
+    pub fn check_avg_chunk_size(size: usize) -> Result<usize, ChunkerError> {
+        match size {
+            MIN_AVG_CHUNK_SIZE..=MAX_AVG_CHUNK_SIZE => Ok(size),
+            _ => Err(ChunkerError::AvgChunkSize(size)),
+        }
+    }
+
+    /// Checks that a maximum chunk size is within the valid range.
+    ///
+    /// # Returns
+    ///
+    /// The size if it is valid, otherwise [`ChunkerError::MaxChunkSize`].
+    pub fn check_max_chunk_size(size: usize) -> Result<usize, ChunkerError> {
+        match size {
+            MIN_MAX_CHUNK_SIZE..=MAX_MAX_CHUNK_SIZE => Ok(size),
+            _ => Err(ChunkerError::MaxChunkSize(size)),
+        }
+    }
+}
+
+impl Display for ChunkSizeParms {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min={} avg={} max={}",
+            self.min_chunk_size, self.avg_chunk_size, self.max_chunk_size
+        )
+    }
+}
+
+/// Invalid parameters of a chunking algorithm.
+///
+/// Returned by the `try_*` constructors of the chunkers, while the other constructors panic with
+/// the message of the error.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ChunkerError {
+    /// The fixed chunk size is zero.
+    ZeroChunkSize,
+    /// The minimum chunk size is out of the valid range.
+    MinChunkSize(usize),
+    /// The average chunk size is out of the valid range.
+    AvgChunkSize(usize),
+    /// The maximum chunk size is out of the valid range.
+    MaxChunkSize(usize),
+    /// The chunk sizes don't satisfy `min <= avg <= max`.
+    UnorderedChunkSizes(ChunkSizeParms),
+    /// The window size is out of the valid range of the algorithm.
+    WindowSize(usize),
+    /// The window size is not a power of two.
+    WindowSizeNotPowerOfTwo(usize),
+    /// The window size is even.
+    EvenWindowSize(usize),
+    /// The jump length is zero or doesn't fit between the minimum and average chunk sizes.
+    JumpLength(usize),
+    /// The degree of the polynomial is out of the valid range.
+    PolynomialDegree(u64),
+    /// The polynomial is not irreducible.
+    ReduciblePolynomial(u64),
+    /// The number of average bits is out of the valid range.
+    AverageBits(u32),
+    /// The number of mask bits is out of the valid range.
+    MaskBits(u32),
+}
+
+impl Display for ChunkerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkerError::ZeroChunkSize => write!(f, "Chunk size must be greater than zero"),
+            ChunkerError::MinChunkSize(size) => {
+                write!(f, "Min chunk size out of valid range: {}", size)
+            }
+            ChunkerError::AvgChunkSize(size) => {
+                write!(f, "Average chunk size out of valid range: {}", size)
+            }
+            ChunkerError::MaxChunkSize(size) => {
+                write!(f, "Max chunk size out of valid range: {}", size)
+            }
+            ChunkerError::UnorderedChunkSizes(parms) => {
+                write!(f, "Chunk sizes must satisfy min <= avg <= max: {}", parms)
+            }
+            ChunkerError::WindowSize(size) => write!(f, "Window size out of valid range: {}", size),
+            ChunkerError::WindowSizeNotPowerOfTwo(size) => {
+                write!(f, "Window size must be a power of two: {}", size)
+            }
+            ChunkerError::EvenWindowSize(size) => write!(f, "Window size must be odd: {}", size),
+            ChunkerError::JumpLength(len) => write!(f, "Jump length out of valid range: {}", len),
+            ChunkerError::PolynomialDegree(polynomial) => {
+                write!(f, "Polynomial degree out of valid range: {:x}", polynomial)
+            }
+            ChunkerError::ReduciblePolynomial(polynomial) => {
+                write!(f, "Polynomial must be irreducible: {:x}", polynomial)
+            }
+            ChunkerError::AverageBits(bits) => {
+                write!(f, "Average bits out of valid range: {}", bits)
+            }
+            ChunkerError::MaskBits(bits) => write!(f, "Mask bits out of valid range: {}", bits),
+        }
+    }
+}
+
+impl std::error::Error for ChunkerError {}
+
 /// Represents the chunk structure for the all chunking algorithms.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct Chunk {
@@ -45,6 +242,58 @@ pub struct Chunk {
     pub length: usize,
 }
 
+/// Common interface of all chunking algorithms.
+///
+/// A `Chunker` only holds the configuration of an algorithm, so it is cheap to copy and can be
+/// reused for any number of sources. The actual chunking is done by the iterator returned from
+/// [`Chunker::chunks`].
+pub trait Chunker {
+    /// Iterator yielding the chunks of a borrowed source.
+    type Iter<'a>: Iterator<Item = Chunk>;
+
+    /// Returns the human-readable name of the algorithm.
+    fn name(&self) -> &'static str;
+
+    /// Returns the chunk size parameters the algorithm works with.
+    fn chunk_parms(&self) -> ChunkSizeParms;
+
+    /// Returns the number of bytes past the end of a chunk the algorithm inspects to place it.
+    ///
+    /// Stream chunkers only emit a chunk once that many bytes following it are known, so that
+    /// its boundary can't move when more data is read.
+    fn lookahead(&self) -> usize {
+        0
+    }
+
+    /// Builds an iterator over the chunks of the provided source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Data buffer to be chunked.
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a>;
+
+    /// Finds the length of the chunk at the start of `data`.
+    ///
+    /// A chunk only depends on the data from its start, so the search is stateless and custom
+    /// pipelines, like ring buffers, memory mapped windows or network frames, can drive the
+    /// chunking by passing the data following the last boundary.
+    ///
+    /// The search treats the end of `data` as the end of the stream. The length is thus final if
+    /// `data` ends the stream, or if the length plus [`Chunker::lookahead`] is less than the length
+    /// of `data`, otherwise the search has to be repeated once more data is known.
+    ///
+    /// The default implementation takes the first chunk of [`Chunker::chunks`], which sets up the
+    /// algorithm on every call. Repeated searches can reuse the setup by calling the `find_cut`
+    /// method of the chunker iterator instead, like [`GearCDC::find_cut`].
+    ///
+    /// # Returns
+    ///
+    /// The length of the chunk, zero only if `data` is empty.
+    fn find_cut(&self, data: &[u8]) -> usize {
+        self.chunks(data).next().map_or(0, |chunk| chunk.length)
+    }
+}
+
 /// Generates a data block of the specified size using the given seed value.
 ///
 /// # Arguments
@@ -67,3 +316,53 @@ pub fn generate_data_block(size: usize, seed_rnd: Option<u128>) -> Vec<u8> {
 
     block
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests the range and order checks of the chunk sizes.
+    #[test]
+    fn test_chunk_size_parms() {
+        assert_eq!(
+            ChunkSizeParms::new(2048, 8192, 65536),
+            Ok(ChunkSizeParms {
+                min_chunk_size: 2048,
+                avg_chunk_size: 8192,
+                max_chunk_size: 65536,
+            })
+        );
+        assert!(ChunkSizeParms::new(64, 256, 1024).is_ok());
+        assert!(ChunkSizeParms::new(8192, 8192, 8192).is_ok());
+
+        assert_eq!(
+            ChunkSizeParms::new(32, 8192, 65536),
+            Err(ChunkerError::MinChunkSize(32))
+        );
+        assert_eq!(
+            ChunkSizeParms::new(2048, 8 * 1024 * 1024, 16 * 1024 * 1024),
+            Err(ChunkerError::AvgChunkSize(8 * 1024 * 1024))
+        );
+        assert_eq!(
+            ChunkSizeParms::new(2048, 8192, 32 * 1024 * 1024),
+            Err(ChunkerError::MaxChunkSize(32 * 1024 * 1024))
+        );
+        assert!(matches!(
+            ChunkSizeParms::new(2048, 65536, 8192),
+            Err(ChunkerError::UnorderedChunkSizes(_))
+        ));
+    }
+
+    /// Tests that the error messages start with the messages the constructors panic with.
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            ChunkerError::MinChunkSize(32).to_string(),
+            "Min chunk size out of valid range: 32"
+        );
+        assert_eq!(
+            ChunkSizeParms::new(2048, 65536, 8192).unwrap_err().to_string(),
+            "Chunk sizes must satisfy min <= avg <= max: min=2048 avg=65536 max=8192"
+        );
+    }
+}
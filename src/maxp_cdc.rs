@@ -0,0 +1,293 @@
+/// Local maximum chunker for content defined data segmentation, after Bjørner, Blass and
+/// Gurevich.
+///
+/// Every position is assigned the big-endian 32-bit word of the four bytes ending at it, and a
+/// chunk ends after a position whose value is strictly greater than the values of all positions
+/// within the horizon on both sides of it. Positions before the chunk start are not considered,
+/// so a chunk only depends on its own bytes and the horizon following it. An edit thus only
+/// moves the boundaries within a horizon around it, and the following chunks are unaffected.
+pub struct MaxpCDC<'a> {
+    /// Number of positions on each side a local maximum has to exceed.
+    horizon: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> MaxpCDC<'a> {
+    /// Constructs a new `MaxpCDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `MaxpCDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::try_new(source, min_size, avg_size, max_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `MaxpCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        Ok(Self {
+            horizon: horizon(min_size, avg_size),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// A candidate exceeded by a following position within the horizon can't be followed by a
+    /// local maximum before that position, so the search continues from there.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let value = |pos: usize| {
+            let bytes = &data[pos.saturating_sub(3)..=pos];
+            bytes
+                .iter()
+                .fold(0u32, |word, &byte| (word << 8) | byte as u32)
+        };
+
+        let mut pos = min_size - 1;
+        while pos < limit {
+            let candidate = value(pos);
+            if (pos.saturating_sub(self.horizon)..pos).any(|left| value(left) >= candidate) {
+                pos += 1;
+                continue;
+            }
+
+            let right_end = (pos + self.horizon + 1).min(remaining);
+            match (pos + 1..right_end).find(|&right| value(right) >= candidate) {
+                Some(right) => pos = right,
+                None => return pos + 1,
+            }
+        }
+
+        limit
+    }
+}
+
+/// Computes the horizon giving the requested average chunk size.
+///
+/// A position of random data is a local maximum with a probability of `1 / (2 * horizon + 1)`,
+/// but positions closer than the horizon to a maximum can't be maxima themselves. With random
+/// data, the distance from the minimum chunk size to the next maximum is about 1.1 horizons.
+fn horizon(min_size: usize, avg_size: usize) -> usize {
+    (avg_size.saturating_sub(min_size) * 10 / 11).max(1)
+}
+
+/// Configuration of the local maximum chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MaxpConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl MaxpConfig {
+    /// Constructs a new `MaxpConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `MaxpConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        MaxpCDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for MaxpConfig {
+    type Iter<'a> = MaxpCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Local Maximum Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    /// A local maximum is only known once the horizon following it has been inspected.
+    fn lookahead(&self) -> usize {
+        horizon(
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+        )
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        MaxpCDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for MaxpCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk ending at a local maximum.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{ChunkSizeParms, Chunker};
+
+    /// Chunk size parameters used by the local maximum tests.
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 1024,
+        avg_chunk_size: 4096,
+        max_chunk_size: 16384,
+    };
+
+    /// Counts the chunks of `edited` whose bytes don't appear as a chunk of `original`.
+    fn count_new_chunks(original: &[u8], edited: &[u8]) -> usize {
+        let config = MaxpConfig::new(PARMS);
+        let known: HashSet<_> = config
+            .chunks(original)
+            .map(|chunk| &original[chunk.offset..chunk.offset + chunk.length])
+            .collect();
+
+        config
+            .chunks(edited)
+            .filter(|chunk| !known.contains(&edited[chunk.offset..chunk.offset + chunk.length]))
+            .count()
+    }
+
+    /// Tests that inserting a single byte only affects the chunks around it.
+    #[test]
+    fn test_insert_affects_bounded_chunks() {
+        let original = crate::generate_data_block(512 * 1024, Some(71));
+
+        for position in [0, 1000, 123_457, 300_000, original.len() - 10] {
+            let mut edited = original.clone();
+            edited.insert(position, 0x5a);
+            let new_chunks = count_new_chunks(&original, &edited);
+            assert!(
+                (1..=3).contains(&new_chunks),
+                "Insert at {} changed {} chunks",
+                position,
+                new_chunks
+            );
+        }
+    }
+
+    /// Tests that every content defined cut is a strict local maximum of the chunk.
+    #[test]
+    fn test_cut_is_local_maximum() {
+        let data = crate::generate_data_block(512 * 1024, Some(72));
+        let chunker = MaxpCDC::new(&data, 256, 1024, 4096);
+        let horizon = chunker.horizon;
+
+        let chunks: Vec<_> = chunker.collect();
+        for chunk in &chunks[..chunks.len() - 1] {
+            if chunk.length == 4096 {
+                continue;
+            }
+            let value = |pos: usize| {
+                let start = pos.max(chunk.offset + 3) - 3;
+                data[start..=pos]
+                    .iter()
+                    .fold(0u32, |word, &byte| (word << 8) | byte as u32)
+            };
+            let end = chunk.offset + chunk.length - 1;
+            let left = end.saturating_sub(horizon).max(chunk.offset);
+            let right = (end + horizon).min(data.len() - 1);
+            assert!((left..=right)
+                .filter(|&pos| pos != end)
+                .all(|pos| value(pos) < value(end)));
+        }
+    }
+
+    /// Tests chunk invariants and the average chunk size on random data.
+    #[test]
+    fn test_random_data_invariants() {
+        let data = crate::generate_data_block(4 * 1024 * 1024 + 1, Some(73));
+        let chunks: Vec<_> = MaxpConfig::new(PARMS).chunks(&data).collect();
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 16384);
+            assert!(chunk.length >= 1024 || i + 1 == chunks.len());
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+
+        let average = data.len() / chunks.len();
+        assert!((3584..=4608).contains(&average), "Average {}", average);
+    }
+}
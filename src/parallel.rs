@@ -0,0 +1,201 @@
+//! Chunking of a single buffer on several threads.
+//!
+//! The buffer is split into stripes, which are chunked concurrently as if each of them started a
+//! new source. Every chunker only looks at the data from the start of a chunk on, so once the
+//! chunks of a stripe start at a boundary of the serial chunking, all their following chunks are
+//! the serial ones too. The stripes are therefore stitched together by chunking serially from the
+//! last boundary before a seam until it meets a boundary of the next stripe, which with content
+//! defined chunking happens within a few chunks.
+
+use super::{Chunk, Chunker};
+
+/// Chunks the source on up to `threads` threads.
+///
+/// The returned chunks are identical to the ones of `chunker.chunks(source)`. Stripes are at
+/// least four maximum chunk sizes long, so small sources and a `threads` of 0 or 1 are chunked
+/// serially on the calling thread.
+///
+/// # Arguments
+///
+/// * `chunker` - The configured chunking algorithm.
+/// * `source` - Data buffer to be chunked.
+/// * `threads` - Maximum number of threads to chunk on.
+///
+/// # Returns
+///
+/// The chunks of the source in order.
+pub fn chunk_parallel<C: Chunker + Sync>(chunker: &C, source: &[u8], threads: usize) -> Vec<Chunk> {
+    let starts = stripe_starts(chunker, source.len(), threads);
+    if starts.len() <= 1 {
+        return chunker.chunks(source).collect();
+    }
+
+    let stripes: Vec<Vec<Chunk>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = starts.get(i + 1).copied().unwrap_or(source.len());
+                scope.spawn(move || chunk_stripe(chunker, source, start, end))
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    reconcile(chunker, source, &stripes)
+}
+
+/// Computes the start positions of the stripes.
+///
+/// The starts are aligned to the average chunk size, so chunkers cutting at fixed distances agree
+/// with the serial boundaries right away.
+fn stripe_starts<C: Chunker>(chunker: &C, len: usize, threads: usize) -> Vec<usize> {
+    let parms = chunker.chunk_parms();
+    let min_stripe_len = 4 * (parms.max_chunk_size + chunker.lookahead());
+    let stripes = threads.min(len / min_stripe_len.max(1)).max(1);
+    let align = parms.avg_chunk_size.max(1);
+
+    let mut starts: Vec<_> = (0..stripes)
+        .map(|i| len * i / stripes / align * align)
+        .collect();
+    starts.dedup();
+    starts
+}
+
+/// Chunks the source from `start` on, until a chunk reaches `end`.
+///
+/// The chunker sees all of the source past `start`, so the last chunk, which crosses into the next
+/// stripe, is placed as in the serial chunking.
+fn chunk_stripe<C: Chunker>(chunker: &C, source: &[u8], start: usize, end: usize) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    for chunk in chunker.chunks(&source[start..]) {
+        let chunk = Chunk {
+            offset: start + chunk.offset,
+            length: chunk.length,
+        };
+        chunks.push(chunk);
+        if chunk.offset + chunk.length >= end {
+            break;
+        }
+    }
+    chunks
+}
+
+/// Stitches the chunks of the stripes together into the serial chunks.
+///
+/// Within each stripe, the serial chunking is continued from the end of the previous stripe until
+/// one of its boundaries is the start of a chunk of the stripe, from where on the chunks of the
+/// stripe are taken.
+fn reconcile<C: Chunker>(chunker: &C, source: &[u8], stripes: &[Vec<Chunk>]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut pos = 0;
+
+    for stripe in stripes {
+        let stripe_end = stripe.last().map_or(0, |chunk| chunk.offset + chunk.length);
+        let base = pos;
+        let mut serial = None;
+
+        while pos < stripe_end {
+            if let Ok(index) = stripe.binary_search_by_key(&pos, |chunk| chunk.offset) {
+                chunks.extend_from_slice(&stripe[index..]);
+                pos = stripe_end;
+                break;
+            }
+
+            let chunk = serial
+                .get_or_insert_with(|| chunker.chunks(&source[base..]))
+                .next()
+                .expect("Serial chunking ended before the stripe");
+            chunks.push(Chunk {
+                offset: base + chunk.offset,
+                length: chunk.length,
+            });
+            pos += chunk.length;
+        }
+    }
+
+    debug_assert_eq!(pos, source.len());
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AEConfig, BuzhashConfig, ChunkSizeParms, FastCDCConfig, FixedSizeConfig, GearConfig,
+        JumpConfig, MaxpConfig, Normalization, RabinConfig, RamConfig, RonomonConfig, RsyncConfig,
+        TttdConfig, V2020Config, DEFAULT_BUZHASH_SEED,
+    };
+
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 512,
+        avg_chunk_size: 2048,
+        max_chunk_size: 8192,
+    };
+
+    /// Asserts that parallel chunking matches serial chunking for several thread counts.
+    fn assert_matches_serial<C: Chunker + Sync>(chunker: C, data: &[u8]) {
+        let serial: Vec<_> = chunker.chunks(data).collect();
+        for threads in [0, 1, 2, 3, 8] {
+            assert_eq!(
+                chunk_parallel(&chunker, data, threads),
+                serial,
+                "{} with {} threads",
+                chunker.name(),
+                threads
+            );
+        }
+    }
+
+    /// Tests that the stripes are stitched into the serial chunks for all chunkers.
+    ///
+    /// The run of zeros makes chunkers cut at the maximum chunk size, so seams within it only meet
+    /// the serial boundaries after the run.
+    #[test]
+    fn test_matches_serial() {
+        let mut data = crate::generate_data_block(1024 * 1024 + 7, Some(151));
+        data[300_000..500_000].fill(0);
+
+        assert_matches_serial(FixedSizeConfig::new(1000), &data);
+        assert_matches_serial(RonomonConfig::new(PARMS), &data);
+        assert_matches_serial(V2020Config::new(PARMS), &data);
+        assert_matches_serial(GearConfig::new(PARMS), &data);
+        assert_matches_serial(FastCDCConfig::new(PARMS, Normalization::Level2), &data);
+        assert_matches_serial(RabinConfig::new(64, PARMS), &data);
+        assert_matches_serial(BuzhashConfig::new(64, DEFAULT_BUZHASH_SEED, PARMS), &data);
+        assert_matches_serial(AEConfig::new(PARMS), &data);
+        assert_matches_serial(RamConfig::new(PARMS), &data);
+        assert_matches_serial(TttdConfig::new(PARMS), &data);
+        assert_matches_serial(RsyncConfig::new(64, PARMS), &data);
+        assert_matches_serial(JumpConfig::new(512, PARMS), &data);
+        assert_matches_serial(MaxpConfig::new(PARMS), &data);
+    }
+
+    /// Tests small and empty sources, which are chunked serially.
+    #[test]
+    fn test_small_sources() {
+        let data = crate::generate_data_block(20_000, Some(152));
+        assert_matches_serial(FastCDCConfig::new(PARMS, Normalization::Level1), &data);
+        assert_matches_serial(FastCDCConfig::new(PARMS, Normalization::Level1), &[]);
+    }
+
+    /// Tests that the stripe starts are aligned and distinct.
+    #[test]
+    fn test_stripe_starts() {
+        let config = FixedSizeConfig::new(1000);
+        assert_eq!(
+            stripe_starts(&config, 100_000, 4),
+            [0, 25_000, 50_000, 75_000]
+        );
+        assert_eq!(stripe_starts(&config, 100_000, 1), [0]);
+        assert_eq!(stripe_starts(&config, 10_000, 8), [0, 5000]);
+    }
+}
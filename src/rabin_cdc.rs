@@ -1,24 +1,250 @@
-/// Constants commonly used in Rabin fingerprint calculations.
-/// Taken from: https://github.com/moinakg/pcompress
-const PRIME: u64 = 153_191u64;
-const MASK: u64 = 0x00ff_ffff_ffffu64;
+use std::ops::Deref;
+use std::sync::Arc;
 
-/// Irreducible polynomial for Rabin modulus, used in pcompress.
-const FP_POLY: u64 = 0xbfe6_b8a5_bf37_8d83u64;
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
+
+/// Irreducible polynomial of degree 53 over GF(2) used as the default Rabin fingerprint modulus.
+/// Taken from: https://github.com/restic/chunker
+pub const FP_POLY: u64 = 0x003d_a335_8b4d_c173u64;
+
+/// Degree of the polynomials generated by [`random_polynomial`].
+const RANDOM_POLY_DEGREE: u32 = 53;
 
 /// Minimum and maximum window sizes for chunking.
 const MIN_WIN_SIZE: usize = 8;
 const MAX_WIN_SIZE: usize = 64;
 
-/// Rabin chunker for data segmentation based on Rabin fingerprinting algorithm.
-pub struct RabinCDC<'a> {
-    /// Precomputed table mapping each output byte to a polynomial value.
-    out_map: Vec<u64>,
-    /// Precomputed irreducible polynomial table for Rabin fingerprint.
-    ir: Vec<u64>,
+/// Tables of the Rabin fingerprint with [`FP_POLY`] for all valid window sizes, computed at
+/// compile time.
+static PRECOMPUTED_TABLES: [RabinTables; 4] = [
+    RabinTables::build(FP_POLY, 8),
+    RabinTables::build(FP_POLY, 16),
+    RabinTables::build(FP_POLY, 32),
+    RabinTables::build(FP_POLY, 64),
+];
+
+/// Precomputed tables rolling the Rabin fingerprint over a window of a given size.
+///
+/// The tables only depend on the polynomial and the window size, so they can be built once and
+/// borrowed by any number of [`RabinCDC`] instances, see [`RabinCDC::with_tables`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RabinTables {
+    /// Table removing the contribution of the byte leaving the window.
+    out_table: [u64; 256],
+    /// Table reducing the fingerprint modulo the polynomial after a byte is appended.
+    mod_table: [u64; 256],
     /// Bit mask for window indexing, facilitating circular buffer behavior.
     win_mask: usize,
-    /// Current position for the sliding window.
+    /// Shift extracting the 8 bits above the degree of the polynomial from the fingerprint.
+    poly_shift: u32,
+    /// Polynomial the fingerprint is computed modulo.
+    polynomial: u64,
+}
+
+impl RabinTables {
+    /// Builds the tables for a window size using [`FP_POLY`].
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    ///
+    /// # Panics
+    ///
+    /// Panic if `win_size` is out of range or not a power of two.
+    pub fn new(win_size: usize) -> Self {
+        Self::with_polynomial(FP_POLY, win_size)
+    }
+
+    /// Builds the tables for a window size using [`FP_POLY`], checking the window size.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `win_size` is out of range or not a power of two.
+    pub fn try_new(win_size: usize) -> Result<Self, super::ChunkerError> {
+        Self::try_with_polynomial(FP_POLY, win_size)
+    }
+
+    /// Builds the tables for a polynomial and a window size.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    ///
+    /// # Panics
+    ///
+    /// Panic if the polynomial is reducible or its degree is out of range, or if `win_size` is out
+    /// of range or not a power of two.
+    pub fn with_polynomial(polynomial: u64, win_size: usize) -> Self {
+        Self::try_with_polynomial(polynomial, win_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Builds the tables for a polynomial and a window size, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polynomial is reducible or its degree is out of range, or if
+    /// `win_size` is out of range or not a power of two.
+    pub fn try_with_polynomial(
+        polynomial: u64,
+        win_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        check_polynomial(polynomial)?;
+        check_win_size(win_size)?;
+        Ok(Self::build(polynomial, win_size))
+    }
+
+    /// Returns the tables for a window size using [`FP_POLY`] computed at compile time.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `win_size` is out of range or not a power of two.
+    pub fn precomputed(win_size: usize) -> Result<&'static Self, super::ChunkerError> {
+        check_win_size(win_size)?;
+        Ok(&PRECOMPUTED_TABLES[(win_size / MIN_WIN_SIZE).ilog2() as usize])
+    }
+
+    /// Returns the size of the sliding window the tables were built for.
+    pub fn win_size(&self) -> usize {
+        self.win_mask + 1
+    }
+
+    /// Returns the polynomial the tables were built for.
+    pub fn polynomial(&self) -> u64 {
+        self.polynomial
+    }
+
+    /// Builds the tables for a valid polynomial and window size.
+    const fn build(polynomial: u64, win_size: usize) -> Self {
+        let degree = poly_degree(polynomial);
+        let mut out_table = [0u64; 256];
+        let mut mod_table = [0u64; 256];
+
+        let mut b = 0;
+        while b < 256 {
+            // out_table[b] = b * x^(8 * (win_size - 1)) mod P, the contribution of the byte `b`
+            // when it is the oldest one in the window.
+            let mut hash = poly_mod(b as u64, polynomial);
+            let mut i = 1;
+            while i < win_size {
+                hash = poly_mod(hash << 8, polynomial);
+                i += 1;
+            }
+            out_table[b] = hash;
+
+            // mod_table[b] = (b * x^deg mod P) | (b * x^deg), so a single XOR both clears the 8
+            // bits above the degree and adds their remainder.
+            let shifted = (b as u64) << degree;
+            mod_table[b] = poly_mod(shifted, polynomial) | shifted;
+            b += 1;
+        }
+
+        Self {
+            out_table,
+            mod_table,
+            win_mask: win_size - 1,
+            poly_shift: degree - 8,
+            polynomial,
+        }
+    }
+}
+
+/// Checks that a polynomial over GF(2) is irreducible and that its degree is in range.
+///
+/// # Returns
+///
+/// The polynomial if it is valid, otherwise [`ChunkerError::PolynomialDegree`] or
+/// [`ChunkerError::ReduciblePolynomial`].
+///
+/// [`ChunkerError::PolynomialDegree`]: super::ChunkerError::PolynomialDegree
+/// [`ChunkerError::ReduciblePolynomial`]: super::ChunkerError::ReduciblePolynomial
+pub fn check_polynomial(polynomial: u64) -> Result<u64, super::ChunkerError> {
+    // The fingerprint shifted by a byte must fit in 64 bits.
+    if polynomial >> 8 == 0 || polynomial >> 57 != 0 {
+        return Err(super::ChunkerError::PolynomialDegree(polynomial));
+    }
+    if !is_irreducible(polynomial) {
+        return Err(super::ChunkerError::ReduciblePolynomial(polynomial));
+    }
+    Ok(polynomial)
+}
+
+/// Tables of a chunker, borrowed or shared with the configuration it was created from.
+#[derive(Debug, Clone)]
+pub(crate) enum TablesRef<'a> {
+    /// Tables borrowed for the lifetime of the chunker, like the ones computed at compile time.
+    Borrowed(&'a RabinTables),
+    /// Tables built at run time and shared by reference counting.
+    Shared(Arc<RabinTables>),
+}
+
+impl TablesRef<'static> {
+    /// Returns the tables computed at compile time for [`FP_POLY`], or builds the tables for
+    /// another polynomial, checking the parameters.
+    pub(crate) fn try_with_polynomial(
+        polynomial: u64,
+        win_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        Ok(match polynomial {
+            FP_POLY => Self::Borrowed(RabinTables::precomputed(win_size)?),
+            _ => Self::Shared(Arc::new(RabinTables::try_with_polynomial(
+                polynomial, win_size,
+            )?)),
+        })
+    }
+}
+
+impl Deref for TablesRef<'_> {
+    type Target = RabinTables;
+
+    fn deref(&self) -> &RabinTables {
+        match self {
+            TablesRef::Borrowed(tables) => tables,
+            TablesRef::Shared(tables) => tables,
+        }
+    }
+}
+
+impl PartialEq for TablesRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for TablesRef<'_> {}
+
+/// Checks that a window size is in range and a power of two.
+fn check_win_size(win_size: usize) -> Result<usize, super::ChunkerError> {
+    if !(MIN_WIN_SIZE..=MAX_WIN_SIZE).contains(&win_size) {
+        return Err(super::ChunkerError::WindowSize(win_size));
+    }
+    if !win_size.is_power_of_two() {
+        return Err(super::ChunkerError::WindowSizeNotPowerOfTwo(win_size));
+    }
+    Ok(win_size)
+}
+
+/// Rabin chunker for data segmentation based on Rabin fingerprinting algorithm.
+///
+/// The fingerprint of the sliding window is the remainder of the window bytes, read as a
+/// polynomial over GF(2), divided by a polynomial, [`FP_POLY`] by default. A chunk ends at the
+/// first position past the minimum chunk size where the low bits of the fingerprint selected by
+/// the cut mask are zero, or at the maximum chunk size if no such position exists. The cut mask
+/// has `log2(avg - min)` bits, so on random data chunks are about `avg` bytes long.
+pub struct RabinCDC<'a> {
+    /// Precomputed tables rolling the fingerprint over the window.
+    tables: TablesRef<'a>,
+    /// Current position in data.
     cur_pos: usize,
     /// Mask used to determine chunk cuts.
     cut_mask: u64,
@@ -29,7 +255,7 @@ pub struct RabinCDC<'a> {
 }
 
 impl<'a> RabinCDC<'a> {
-    /// Constructs a new `RabinCDC`.
+    /// Constructs a new `RabinCDC` using the tables computed at compile time.
     ///
     /// # Arguments
     /// * `source` - Data buffer to be chunked.
@@ -52,123 +278,434 @@ impl<'a> RabinCDC<'a> {
         avg_size: usize,
         max_size: usize,
     ) -> Self {
-        // Assertions to ensure the parameters are within expected bounds
-        assert!(
-            (MIN_WIN_SIZE..=MAX_WIN_SIZE).contains(&win_size),
-            "Window size out of valid range"
-        );
-        assert!(
-            win_size & (win_size - 1) == 0 && win_size != 0,
-            "Window size must be a power of two"
-        );
-        assert!(
-            (super::MIN_MIN_CHUNK_SIZE..=super::MAX_MIN_CHUNK_SIZE).contains(&min_size),
-            "Min chunk size out of valid range"
-        );
-        assert!(
-            (super::MIN_AVG_CHUNK_SIZE..=super::MAX_AVG_CHUNK_SIZE).contains(&avg_size),
-            "Average chunk size out of valid range"
-        );
-        assert!(
-            (super::MIN_MAX_CHUNK_SIZE..=super::MAX_MAX_CHUNK_SIZE).contains(&max_size),
-            "Max chunk size out of valid range"
-        );
+        Self::try_new(source, win_size, min_size, avg_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `RabinCDC` using the tables computed at compile time, checking the
+    /// parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated or if `win_size` is not a
+    /// power of two.
+    pub fn try_new(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_polynomial(source, FP_POLY, win_size, min_size, avg_size, max_size)
+    }
+
+    /// Constructs a new `RabinCDC` using a custom polynomial.
+    ///
+    /// The tables are built for the polynomial unless it is [`FP_POLY`], use
+    /// [`RabinCDC::with_tables`] to share them between chunkers.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated, if the polynomial is reducible or its
+    /// degree is out of range or if `win_size` is not a power of two.
+    ///
+    /// # Returns
+    ///
+    /// A new `RabinCDC` instance.
+    pub fn with_polynomial(
+        source: &'a [u8],
+        polynomial: u64,
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_with_polynomial(source, polynomial, win_size, min_size, avg_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `RabinCDC` using a custom polynomial, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated, if the polynomial is
+    /// reducible or its degree is out of range or if `win_size` is not a power of two.
+    pub fn try_with_polynomial(
+        source: &'a [u8],
+        polynomial: u64,
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let tables = TablesRef::try_with_polynomial(polynomial, win_size)?;
+        Self::try_with_tables_ref(source, tables, min_size, avg_size, max_size)
+    }
 
-        let poly_pow = (1..=win_size).fold(1u64, |acc, _| (acc * PRIME) & MASK);
+    /// Constructs a new `RabinCDC` borrowing prebuilt tables.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `tables` - Tables rolling the fingerprint over the window.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `RabinCDC` instance.
+    pub fn with_tables(
+        source: &'a [u8],
+        tables: &'a RabinTables,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_with_tables(source, tables, min_size, avg_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `RabinCDC` borrowing prebuilt tables, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `tables` - Tables rolling the fingerprint over the window.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_with_tables(
+        source: &'a [u8],
+        tables: &'a RabinTables,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let tables = TablesRef::Borrowed(tables);
+        Self::try_with_tables_ref(source, tables, min_size, avg_size, max_size)
+    }
 
-        let out_map = (0..256)
-            .map(|i| (i as u64 * poly_pow) & MASK)
-            .collect::<Vec<u64>>();
+    /// Constructs a new `RabinCDC` with borrowed or shared tables, checking the chunk sizes.
+    pub(crate) fn try_with_tables_ref(
+        source: &'a [u8],
+        tables: TablesRef<'a>,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
 
-        let ir = (0..256)
-            .map(|_| {
-                (0..win_size).fold(1u64, |acc, _| {
-                    if acc & FP_POLY != 0 {
-                        (acc + (acc * PRIME)) & MASK
-                    } else {
-                        (acc * PRIME) & MASK
-                    }
-                })
-            })
-            .collect::<Vec<u64>>();
+        // Expected chunk length is the minimum size plus 2^bits.
+        let target = avg_size.saturating_sub(min_size).max(1);
+        let bits = (target as f64).log2().round() as u32;
 
-        Self {
-            out_map,
-            ir,
-            win_mask: win_size - 1,
+        Ok(Self {
+            tables,
             cur_pos: 0,
-            cut_mask: (avg_size - min_size - 1) as u64,
+            cut_mask: (1u64 << bits) - 1,
             source,
-            chunk_parms: super::ChunkSizeParms {
-                min_chunk_size: min_size,
-                avg_chunk_size: avg_size,
-                max_chunk_size: max_size,
-            },
+            chunk_parms,
+        })
+    }
+
+    /// Replaces the cut mask derived from the average chunk size with a mask of `bits` bits.
+    pub(crate) fn with_cut_bits(mut self, bits: u32) -> Self {
+        self.cut_mask = (1u64 << bits) - 1;
+        self
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// Only the last window before the minimum chunk size is hashed ahead of the first cut check,
+    /// since earlier bytes can't influence the fingerprint.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+
+        let tables = &*self.tables;
+        let mut window = [0u8; MAX_WIN_SIZE];
+        let mut window_index = 0;
+        let mut hash = 0u64;
+
+        for (pos, &byte) in data.iter().enumerate().skip(min_size - tables.win_mask - 1) {
+            let out_byte = window[window_index];
+            window[window_index] = byte;
+            window_index = (window_index + 1) & tables.win_mask;
+
+            hash ^= tables.out_table[out_byte as usize];
+            let index = (hash >> tables.poly_shift) as usize;
+            hash = ((hash << 8) | u64::from(byte)) ^ tables.mod_table[index];
+
+            if pos + 1 >= min_size && hash & self.cut_mask == 0 {
+                return pos + 1;
+            }
         }
+
+        limit
     }
 }
 
-impl Iterator for RabinCDC<'_> {
-    type Item = super::Chunk;
+/// Generates a random irreducible polynomial of degree 53 over GF(2) from a seed.
+///
+/// Like restic, random polynomials of degree 53 with the constant term set are drawn until one is
+/// irreducible, which takes about 26 attempts on average. The same seed always yields the same
+/// polynomial.
+///
+/// # Arguments
+/// * `seed` - Seed of the random generator.
+pub fn random_polynomial(seed: u64) -> u64 {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    loop {
+        let polynomial =
+            rng.next_u64() & ((1 << RANDOM_POLY_DEGREE) - 1) | 1 << RANDOM_POLY_DEGREE | 1;
+        if is_irreducible(polynomial) {
+            return polynomial;
+        }
+    }
+}
 
-    /// Computes the next chunk based on the Rabin fingerprint.
+/// Checks whether a polynomial over GF(2) is irreducible.
+///
+/// Uses the test of Ben-Or: a polynomial `f` of degree `n` is irreducible if and only if
+/// `gcd(x^(2^i) - x, f) = 1` for every `i` from 1 to `n / 2`.
+pub fn is_irreducible(polynomial: u64) -> bool {
+    if polynomial < 2 {
+        return false;
+    }
+
+    // x^(2^i) mod f, starting with i = 0.
+    let mut power = poly_mod(0b10, polynomial);
+    for _ in 0..poly_degree(polynomial) / 2 {
+        power = poly_mul_mod(power, power, polynomial);
+        if poly_gcd(power ^ 0b10, polynomial) != 1 {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns the product of two polynomials over GF(2) modulo a third one.
+///
+/// Both factors must be reduced modulo the polynomial, whose degree must be below 63.
+fn poly_mul_mod(mut a: u64, mut b: u64, modulus: u64) -> u64 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = poly_mod(a << 1, modulus);
+        b >>= 1;
+    }
+    product
+}
+
+/// Returns the greatest common divisor of two polynomials over GF(2).
+fn poly_gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, poly_mod(a, b));
+    }
+    a
+}
+
+/// Returns the degree of the polynomial over GF(2).
+pub(crate) const fn poly_degree(poly: u64) -> u32 {
+    63 - poly.leading_zeros()
+}
+
+/// Returns the remainder of the polynomial division over GF(2).
+pub(crate) const fn poly_mod(mut value: u64, modulus: u64) -> u64 {
+    let degree = poly_degree(modulus);
+    while value != 0 && poly_degree(value) >= degree {
+        value ^= modulus << (poly_degree(value) - degree);
+    }
+    value
+}
+
+/// Configuration of the Rabin chunking algorithm.
+///
+/// The tables are built once when the configuration is created and shared by the chunkers of
+/// all buffers, so the configuration is cheap to clone.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RabinConfig {
+    /// Tables rolling the fingerprint over the window.
+    tables: TablesRef<'static>,
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl RabinConfig {
+    /// Constructs a new `RabinConfig` using [`FP_POLY`].
     ///
-    /// # Returns
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
     ///
-    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
-    /// end of data is reached.
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.source[self.cur_pos..].len() <= self.cur_pos {
-            return None;
-        }
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated or if `win_size` is not a power of two.
+    pub fn new(win_size: usize, chunk_parms: super::ChunkSizeParms) -> Self {
+        Self::with_polynomial(FP_POLY, win_size, chunk_parms)
+    }
+
+    /// Constructs a new `RabinConfig` using [`FP_POLY`], checking the parameters.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated or if `win_size` is not a
+    /// power of two.
+    pub fn try_new(
+        win_size: usize,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        Self::try_with_polynomial(FP_POLY, win_size, chunk_parms)
+    }
 
-        let data_remain = self.source.len() - self.cur_pos;
-        if data_remain < self.chunk_parms.min_chunk_size {
-            let offset = self.cur_pos;
-            let length = self.source[self.cur_pos..].len() - self.cur_pos;
-            self.cur_pos = self.source.len();
+    /// Constructs a new `RabinConfig` using a custom polynomial.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated, if the polynomial is reducible or its
+    /// degree is out of range or if `win_size` is not a power of two.
+    pub fn with_polynomial(
+        polynomial: u64,
+        win_size: usize,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Self {
+        Self::try_with_polynomial(polynomial, win_size, chunk_parms)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
 
-            return Some(super::Chunk { offset, length });
-        }
+    /// Constructs a new `RabinConfig` using a custom polynomial, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of degree 8 to 56, see
+    ///   [`random_polynomial`].
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated, if the polynomial is
+    /// reducible or its degree is out of range or if `win_size` is not a power of two.
+    pub fn try_with_polynomial(
+        polynomial: u64,
+        win_size: usize,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        let tables = TablesRef::try_with_polynomial(polynomial, win_size)?;
+        RabinCDC::try_with_tables(
+            &[],
+            &tables,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self {
+            tables,
+            chunk_parms,
+        })
+    }
 
-        let max_chunk_limit = std::cmp::min(
-            self.chunk_parms.max_chunk_size,
-            self.source[self.cur_pos..].len(),
-        );
-        let mut current_position = self.cur_pos;
+    /// Returns the polynomial the fingerprint is computed modulo.
+    pub fn polynomial(&self) -> u64 {
+        self.tables.polynomial()
+    }
 
-        let mut window = [0u8; MAX_WIN_SIZE];
-        let mut window_index = 0;
-        let mut rolling_hash = 0u64;
+    /// Returns the size of the sliding window.
+    pub fn win_size(&self) -> usize {
+        self.tables.win_size()
+    }
+}
 
-        while current_position < max_chunk_limit {
-            let byte = self.source[current_position];
-            let out_byte = window[window_index] as usize;
-            let out_value = self.out_map[out_byte];
+impl super::Chunker for RabinConfig {
+    type Iter<'a> = RabinCDC<'a>;
 
-            rolling_hash = ((rolling_hash * PRIME) & MASK)
-                .wrapping_add(u64::from(byte))
-                .wrapping_sub(out_value)
-                & MASK;
+    fn name(&self) -> &'static str {
+        "Rabin Content Defined Chunking"
+    }
 
-            window[window_index] = byte;
-            window_index = (window_index + 1) & self.win_mask;
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
 
-            if current_position - self.cur_pos + 1 >= self.chunk_parms.min_chunk_size {
-                let checksum = rolling_hash ^ self.ir[out_byte];
-                if (checksum & self.cut_mask) == 0 {
-                    let offset = self.cur_pos;
-                    let length = current_position - self.cur_pos + 1;
-                    self.cur_pos = current_position + 1;
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        RabinCDC::try_with_tables_ref(
+            source,
+            self.tables.clone(),
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+        .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
 
-                    return Some(super::Chunk { offset, length });
-                }
-            }
-            current_position += 1;
+impl Iterator for RabinCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Rabin fingerprint.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
         }
 
         let offset = self.cur_pos;
-        let length = max_chunk_limit;
+        let length = self.find_cut(&self.source[offset..]);
         self.cur_pos += length;
 
         Some(super::Chunk { offset, length })
@@ -242,6 +779,290 @@ mod tests {
         );
     }
 
+    /// Checks that chunks are contiguous, cover the whole source and respect the size limits.
+    fn assert_chunk_invariants(chunks: &[crate::Chunk], len: usize, min: usize, max: usize) {
+        let mut expected_offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(
+                chunk.offset, expected_offset,
+                "Chunk {} is not contiguous",
+                i
+            );
+            assert!(chunk.length > 0, "Chunk {} is empty", i);
+            assert!(chunk.length <= max, "Chunk {} exceeds the maximum size", i);
+            if i + 1 < chunks.len() {
+                assert!(chunk.length >= min, "Chunk {} is below the minimum size", i);
+            }
+            expected_offset += chunk.length;
+        }
+        assert_eq!(expected_offset, len, "Chunks must cover the whole source");
+    }
+
+    /// Tests chunk invariants on random data for various parameters.
+    #[test]
+    fn test_random_data_invariants() {
+        let parms = [
+            (8, 64, 256, 1024),
+            (16, 512, 2048, 8192),
+            (32, 2048, 8192, 65536),
+            (64, 8192, 10240, 65536),
+            (64, 4096, 4096, 4096),
+        ];
+
+        for (seed, (win, min, avg, max)) in parms.into_iter().enumerate() {
+            for len in [0, 1, min - 1, min, min + 1, max, max + 1, 1024 * 1024 + 7] {
+                let data = crate::generate_data_block(len, Some(seed as u128));
+                let chunks: Vec<_> = RabinCDC::new(&data, win, min, avg, max).collect();
+                assert_chunk_invariants(&chunks, len, min, max);
+            }
+        }
+    }
+
+    /// Tests that the average chunk size of random data is close to the requested one.
+    #[test]
+    fn test_random_data_average() {
+        let data = crate::generate_data_block(8 * 1024 * 1024, Some(42));
+        let chunks: Vec<_> = RabinCDC::new(&data, 64, 2048, 10240, 65536).collect();
+
+        let average = data.len() / chunks.len();
+        assert!(
+            (8192..=12288).contains(&average),
+            "Average chunk size {} is too far from 10240",
+            average
+        );
+    }
+
+    /// Tests that the rolling fingerprint matches the fingerprint computed from scratch.
+    #[test]
+    fn test_rolling_fingerprint() {
+        let data = crate::generate_data_block(4096, Some(5));
+
+        // Irreducible polynomials of the lowest and highest valid degrees, the default and a
+        // random one.
+        for polynomial in [0x11B, FP_POLY, random_polynomial(5), 0x0100_0000_0000_0095] {
+            let tables = RabinTables::with_polynomial(polynomial, 16);
+
+            let mut window = [0u8; MAX_WIN_SIZE];
+            let mut window_index = 0;
+            let mut hash = 0u64;
+            for (pos, &byte) in data.iter().enumerate() {
+                let out_byte = window[window_index];
+                window[window_index] = byte;
+                window_index = (window_index + 1) & tables.win_mask;
+                hash ^= tables.out_table[out_byte as usize];
+                let index = (hash >> tables.poly_shift) as usize;
+                hash = ((hash << 8) | u64::from(byte)) ^ tables.mod_table[index];
+
+                let start = (pos + 1).saturating_sub(16);
+                let expected = data[start..=pos].iter().fold(0u64, |acc, &b| {
+                    poly_mod((acc << 8) | u64::from(b), polynomial)
+                });
+                assert_eq!(
+                    hash, expected,
+                    "Fingerprint mismatch at {} with {:x}",
+                    pos, polynomial
+                );
+            }
+        }
+    }
+
+    /// Tests the irreducibility check with polynomials of known factorization.
+    #[test]
+    fn test_is_irreducible() {
+        // x^2 + x + 1, x^4 + x + 1, x^8 + x^4 + x^3 + x + 1 and the default polynomial
+        for polynomial in [0b111, 0x13, 0x11B, FP_POLY] {
+            assert!(
+                is_irreducible(polynomial),
+                "{:x} is irreducible",
+                polynomial
+            );
+        }
+        // x^2 + 1 = (x + 1)^2, x^4 + x^2 + 1 = (x^2 + x + 1)^2, x^3 + x = x(x + 1)^2, and the
+        // product of the default polynomial with x + 1
+        let product = FP_POLY << 1 ^ FP_POLY;
+        for polynomial in [0, 1, 0b101, 0x15, 0b1010, product] {
+            assert!(!is_irreducible(polynomial), "{:x} is reducible", polynomial);
+        }
+    }
+
+    /// Tests that random polynomials are irreducible of degree 53 and determined by the seed.
+    #[test]
+    fn test_random_polynomial() {
+        let polynomials: Vec<_> = (0..16).map(random_polynomial).collect();
+        for &polynomial in &polynomials {
+            assert_eq!(poly_degree(polynomial), 53, "Degree of {:x}", polynomial);
+            assert!(
+                is_irreducible(polynomial),
+                "{:x} is irreducible",
+                polynomial
+            );
+        }
+        assert_eq!(
+            polynomials,
+            (0..16).map(random_polynomial).collect::<Vec<_>>()
+        );
+
+        let distinct: std::collections::HashSet<_> = polynomials.iter().collect();
+        assert_eq!(
+            distinct.len(),
+            polynomials.len(),
+            "Seeds must give distinct polynomials"
+        );
+    }
+
+    /// Tests that the polynomial changes the boundaries, and that the default polynomial gives
+    /// the same boundaries as the default constructor.
+    #[test]
+    fn test_polynomial() {
+        use crate::{ChunkSizeParms, Chunker, ChunkerError};
+
+        let data = crate::generate_data_block(256 * 1024, Some(12));
+        let parms = ChunkSizeParms::new(512, 2048, 8192).unwrap();
+        let chunks = |config: RabinConfig| config.chunks(&data).collect::<Vec<_>>();
+
+        let default = chunks(RabinConfig::new(64, parms));
+        assert_eq!(
+            default,
+            chunks(RabinConfig::with_polynomial(FP_POLY, 64, parms))
+        );
+
+        let polynomial = random_polynomial(12);
+        let random = chunks(RabinConfig::with_polynomial(polynomial, 64, parms));
+        assert_ne!(random, default, "Polynomial doesn't change the boundaries");
+
+        let tables = RabinTables::with_polynomial(polynomial, 64);
+        assert_eq!(tables.polynomial(), polynomial);
+        let shared: Vec<_> = RabinCDC::with_tables(&data, &tables, 512, 2048, 8192).collect();
+        assert_eq!(shared, random);
+
+        assert_eq!(
+            RabinConfig::try_with_polynomial(0xFF, 64, parms),
+            Err(ChunkerError::PolynomialDegree(0xFF))
+        );
+        assert_eq!(
+            RabinTables::try_with_polynomial(1 << 57, 64).err(),
+            Some(ChunkerError::PolynomialDegree(1 << 57))
+        );
+    }
+
+    /// Tests that a reducible polynomial of degree 53 is rejected by every constructor taking a
+    /// polynomial.
+    #[test]
+    fn test_reducible_polynomial() {
+        use crate::{ChunkSizeParms, ChunkerError, ResticCDC, ResticConfig};
+
+        // An even number of terms makes x + 1 a factor.
+        let polynomial = 1 << 53 | 0b111;
+        assert!(!is_irreducible(polynomial));
+        let error = Some(ChunkerError::ReduciblePolynomial(polynomial));
+
+        let parms = ChunkSizeParms::new(512, 2048, 8192).unwrap();
+        assert_eq!(check_polynomial(polynomial).err(), error);
+        assert_eq!(
+            RabinTables::try_with_polynomial(polynomial, 64).err(),
+            error
+        );
+        assert_eq!(
+            RabinConfig::try_with_polynomial(polynomial, 64, parms).err(),
+            error
+        );
+        assert_eq!(
+            RabinCDC::try_with_polynomial(&[], polynomial, 64, 512, 2048, 8192).err(),
+            error
+        );
+        assert_eq!(
+            ResticConfig::try_with_boundaries(polynomial, 12, 4096, 65536).err(),
+            error
+        );
+        assert_eq!(
+            ResticCDC::try_new(&[], polynomial, 12, 4096, 65536).err(),
+            error
+        );
+        assert_eq!(check_polynomial(FP_POLY), Ok(FP_POLY));
+    }
+
+    /// Tests that the tables computed at compile time match the ones built at run time, and that
+    /// chunkers sharing tables find the same boundaries as chunkers using their own.
+    #[test]
+    fn test_shared_tables() {
+        let data = crate::generate_data_block(256 * 1024, Some(11));
+
+        for win_size in [8, 16, 32, 64] {
+            let tables = RabinTables::new(win_size);
+            let precomputed = RabinTables::precomputed(win_size).unwrap();
+            assert_eq!(
+                &tables, precomputed,
+                "Tables differ for window {}",
+                win_size
+            );
+            assert_eq!(tables.win_size(), win_size);
+
+            for part in data.chunks(64 * 1024) {
+                let expected: Vec<_> = RabinCDC::new(part, win_size, 512, 2048, 8192).collect();
+                let chunks: Vec<_> =
+                    RabinCDC::with_tables(part, &tables, 512, 2048, 8192).collect();
+                assert_eq!(chunks, expected, "Shared tables change the boundaries");
+            }
+        }
+        assert_eq!(
+            RabinTables::precomputed(48).err(),
+            Some(crate::ChunkerError::WindowSizeNotPowerOfTwo(48))
+        );
+    }
+
+    /// Tests that the tables of a configuration are built once and shared by its clones and by
+    /// the chunkers of every buffer.
+    #[test]
+    fn test_config_tables() {
+        use crate::Chunker;
+
+        let parms = crate::ChunkSizeParms::new(512, 2048, 8192).unwrap();
+        let polynomial = random_polynomial(17);
+        let config = RabinConfig::with_polynomial(polynomial, 32, parms);
+        assert_eq!(config.polynomial(), polynomial);
+        assert_eq!(config.win_size(), 32);
+        assert!(matches!(config.tables, TablesRef::Shared(_)));
+
+        let data = crate::generate_data_block(64 * 1024, Some(17));
+        let clone = config.clone();
+        for chunker in [config.chunks(&data), clone.chunks(&data[1000..])] {
+            assert!(std::ptr::eq(&*chunker.tables, &*config.tables));
+        }
+        let expected: Vec<_> =
+            RabinCDC::with_polynomial(&data, polynomial, 32, 512, 2048, 8192).collect();
+        assert_eq!(config.chunks(&data).collect::<Vec<_>>(), expected);
+
+        let config = RabinConfig::new(64, parms);
+        assert!(std::ptr::eq(
+            &*config.chunks(&data).tables,
+            RabinTables::precomputed(64).unwrap()
+        ));
+    }
+
+    /// Tests that boundaries are content defined and resynchronize after an insertion.
+    #[test]
+    fn test_boundary_shift() {
+        let data = crate::generate_data_block(1024 * 1024, Some(9));
+        let mut edited = vec![0xAB; 100];
+        edited.extend_from_slice(&data);
+
+        let ends = |source: &[u8], shift: usize| {
+            RabinCDC::new(source, 64, 512, 2048, 8192)
+                .map(|chunk| chunk.offset + chunk.length - shift)
+                .collect::<std::collections::HashSet<_>>()
+        };
+        let original = ends(&data, 0);
+        let shifted = ends(&edited, 100);
+
+        let common = original.intersection(&shifted).count();
+        assert!(
+            common + 3 >= original.len(),
+            "Only {} of {} boundaries survived an insertion",
+            common,
+            original.len()
+        );
+    }
+
     /// Tests RabinCDC with an invalid window size (not a power of two).
     #[test]
     #[should_panic(expected = "Window size must be a power of two")]
@@ -265,4 +1086,36 @@ mod tests {
         let data = generate_test_data(1000);
         let _chunker = RabinCDC::new(&data, 0, 50, 100, 200);
     }
+
+    /// Tests that the fallible constructor reports invalid parameters instead of panicking.
+    #[test]
+    fn test_try_new() {
+        use crate::{ChunkSizeParms, ChunkerError};
+
+        let data = generate_test_data(1000);
+        assert!(RabinCDC::try_new(&data, 64, 512, 2048, 8192).is_ok());
+        assert_eq!(
+            RabinCDC::try_new(&data, 50, 512, 2048, 8192).err(),
+            Some(ChunkerError::WindowSizeNotPowerOfTwo(50))
+        );
+        assert_eq!(
+            RabinCDC::try_new(&data, 64, 50, 2048, 8192).err(),
+            Some(ChunkerError::MinChunkSize(50))
+        );
+        assert_eq!(
+            RabinCDC::try_new(&data, 64, 4096, 2048, 8192).err(),
+            Some(ChunkerError::UnorderedChunkSizes(ChunkSizeParms {
+                min_chunk_size: 4096,
+                avg_chunk_size: 2048,
+                max_chunk_size: 8192,
+            }))
+        );
+
+        let parms = ChunkSizeParms::new(512, 2048, 8192).unwrap();
+        assert_eq!(
+            RabinConfig::try_new(64, parms),
+            Ok(RabinConfig::new(64, parms))
+        );
+        assert!(RabinConfig::try_new(32 * 1024, parms).is_err());
+    }
 }
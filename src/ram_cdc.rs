@@ -0,0 +1,243 @@
+/// Rapid Asymmetric Maximum chunker for hashless content defined data segmentation.
+///
+/// The chunker finds the maximum byte value of a fixed-size window at the beginning of the chunk
+/// and cuts after the first following byte that is not smaller than that maximum, or at the
+/// maximum chunk size if no such byte exists. The window is sized so that the expected chunk
+/// length of random data matches the average chunk size, but never shorter than the minimum chunk
+/// size.
+pub struct RamCDC<'a> {
+    /// Size of the window the maximum is taken from.
+    window: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> RamCDC<'a> {
+    /// Constructs a new `RamCDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `RamCDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::try_new(source, min_size, avg_size, max_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `RamCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        Ok(Self {
+            window: window_size(avg_size).max(min_size),
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        if limit <= self.window {
+            return limit;
+        }
+
+        let data = &data[..limit];
+        let max_value = data[..self.window].iter().copied().max().unwrap_or(0);
+
+        data[self.window..]
+            .iter()
+            .position(|&byte| byte >= max_value)
+            .map_or(limit, |pos| self.window + pos + 1)
+    }
+}
+
+/// Computes the window giving the requested average chunk size for random data.
+///
+/// When the maximum of the window is `m`, each following byte ends the chunk with probability
+/// `(256 - m) / 256`, so the expected chunk length is the window plus the mean of that geometric
+/// distribution over the distribution of `m`. The smallest window reaching the average is
+/// searched for, as the expected length grows with the window.
+fn window_size(avg_size: usize) -> usize {
+    let expected_length = |window: usize| {
+        let cdf = |m: usize| (m as f64 / 256.0).powi(window as i32);
+        let tail: f64 = (0..256)
+            .map(|m| (cdf(m + 1) - cdf(m)) * 256.0 / (256 - m) as f64)
+            .sum();
+        window as f64 + tail
+    };
+
+    let (mut low, mut high) = (1, avg_size);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if expected_length(mid) < avg_size as f64 {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// Configuration of the Rapid Asymmetric Maximum chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RamConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl RamConfig {
+    /// Constructs a new `RamConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `RamConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        RamCDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for RamConfig {
+    type Iter<'a> = RamCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Rapid Asymmetric Maximum Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        RamCDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for RamCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the window maximum.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that every content defined cut is the first byte reaching the window maximum.
+    #[test]
+    fn test_cut_reaches_window_maximum() {
+        let data = crate::generate_data_block(512 * 1024, Some(51));
+        let chunker = RamCDC::new(&data, 256, 1024, 4096);
+        let window = chunker.window;
+
+        let chunks: Vec<_> = chunker.collect();
+        for chunk in &chunks[..chunks.len() - 1] {
+            if chunk.length == 4096 {
+                continue;
+            }
+            let bytes = &data[chunk.offset..chunk.offset + chunk.length];
+            let max_value = *bytes[..window].iter().max().unwrap();
+            let (last, middle) = bytes[window..].split_last().unwrap();
+            assert!(*last >= max_value, "Chunk at {}", chunk.offset);
+            assert!(middle.iter().all(|&b| b < max_value));
+        }
+    }
+
+    /// Tests that the average chunk size of random data is close to the requested one.
+    #[test]
+    fn test_random_average() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(52));
+        let chunks: Vec<_> = RamCDC::new(&data, 64, 8192, 65536).collect();
+
+        let mut offset = 0;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 65536);
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+
+        let average = data.len() / chunks.len();
+        assert!((7168..=9216).contains(&average), "Average {}", average);
+    }
+
+    /// Tests that constant data is cut right after the window.
+    #[test]
+    fn test_constant_data() {
+        let data = vec![0u8; 10_000];
+        let chunks: Vec<_> = RamCDC::new(&data, 1024, 1024, 4096).collect();
+
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.length == 1025));
+    }
+}
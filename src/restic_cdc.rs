@@ -0,0 +1,424 @@
+use super::rabin_cdc::{check_polynomial, RabinCDC, RabinTables, TablesRef};
+
+/// Size of the sliding window of the restic chunker.
+const WINDOW_SIZE: usize = 64;
+
+/// Minimum chunk size used by restic.
+pub const RESTIC_MIN_SIZE: usize = 512 * 1024;
+
+/// Maximum chunk size used by restic.
+pub const RESTIC_MAX_SIZE: usize = 8 * 1024 * 1024;
+
+/// Number of fingerprint bits that must be zero at a cut point in restic.
+pub const RESTIC_AVERAGE_BITS: u32 = 20;
+
+/// Rabin chunker following the chunker of restic.
+///
+/// The chunker is a preset of [`RabinCDC`] with a 64-byte window, and a chunk ends where the low
+/// `average_bits` bits of the fingerprint are zero, or at the maximum chunk size. restic computes
+/// the fingerprint modulo a polynomial of degree 53, which it picks at random for every
+/// repository. Every restic chunk starts with a window holding a single one byte, which leaves the
+/// window with the last byte before the minimum chunk size, so the fingerprint at every cut check
+/// is the one of the plain window. Only the chunks of zeros are checked against restic so far.
+pub struct ResticCDC<'a> {
+    /// Rabin chunker with the window and cut mask of restic.
+    inner: RabinCDC<'a>,
+}
+
+impl<'a> ResticCDC<'a> {
+    /// Constructs a new `ResticCDC`.
+    ///
+    /// The tables are built for the polynomial unless it is [`FP_POLY`](super::FP_POLY), use
+    /// [`ResticCDC::with_tables`] to share them between chunkers.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the polynomial is reducible or its degree is not between 8 and 56, or if any of
+    /// the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `ResticCDC` instance.
+    pub fn new(
+        source: &'a [u8],
+        polynomial: u64,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_new(source, polynomial, average_bits, min_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `ResticCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polynomial is reducible or its degree is not between 8 and 56, or
+    /// if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        polynomial: u64,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        check_polynomial(polynomial)?;
+        let chunk_parms = restic_chunk_parms(average_bits, min_size, max_size)?;
+        let inner = RabinCDC::try_with_polynomial(
+            source,
+            polynomial,
+            WINDOW_SIZE,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+
+        Ok(Self {
+            inner: inner.with_cut_bits(average_bits),
+        })
+    }
+
+    /// Constructs a new `ResticCDC` borrowing prebuilt tables.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `tables` - Tables of the polynomial of the restic repository for a 64-byte window.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the tables aren't built for a 64-byte window, or if any of the size constraints
+    /// are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `ResticCDC` instance.
+    pub fn with_tables(
+        source: &'a [u8],
+        tables: &'a RabinTables,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_with_tables(source, tables, average_bits, min_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `ResticCDC` borrowing prebuilt tables, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `tables` - Tables of the polynomial of the restic repository for a 64-byte window.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tables aren't built for a 64-byte window, or if any of the size
+    /// constraints are violated.
+    pub fn try_with_tables(
+        source: &'a [u8],
+        tables: &'a RabinTables,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        if tables.win_size() != WINDOW_SIZE {
+            return Err(super::ChunkerError::WindowSize(tables.win_size()));
+        }
+        let chunk_parms = restic_chunk_parms(average_bits, min_size, max_size)?;
+        let inner = RabinCDC::try_with_tables(
+            source,
+            tables,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+
+        Ok(Self {
+            inner: inner.with_cut_bits(average_bits),
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        self.inner.find_cut(data)
+    }
+}
+
+/// Checks the chunk sizes of the restic chunker.
+///
+/// # Returns
+///
+/// The chunk sizes, with the minimum size plus `2^average_bits` as the average size, limited to
+/// the maximum size.
+fn restic_chunk_parms(
+    average_bits: u32,
+    min_size: usize,
+    max_size: usize,
+) -> Result<super::ChunkSizeParms, super::ChunkerError> {
+    if !(1..=30).contains(&average_bits) {
+        return Err(super::ChunkerError::AverageBits(average_bits));
+    }
+    super::ChunkSizeParms::new(
+        min_size,
+        min_size.saturating_add(1 << average_bits).min(max_size),
+        max_size,
+    )
+}
+
+/// Configuration of the restic chunking algorithm.
+///
+/// The tables are built once when the configuration is created and shared by the chunkers of
+/// all buffers, so the configuration is cheap to clone.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ResticConfig {
+    /// Tables of the polynomial of the restic repository.
+    tables: TablesRef<'static>,
+    /// Number of fingerprint bits that must be zero at a cut point.
+    pub average_bits: u32,
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl ResticConfig {
+    /// Constructs a new `ResticConfig` with the chunk sizes used by restic.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    pub fn new(polynomial: u64) -> Self {
+        Self::with_boundaries(
+            polynomial,
+            RESTIC_AVERAGE_BITS,
+            RESTIC_MIN_SIZE,
+            RESTIC_MAX_SIZE,
+        )
+    }
+
+    /// Constructs a new `ResticConfig` with the chunk sizes used by restic,
+    /// checking the parameters.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polynomial is reducible or its degree is not between 8 and 56.
+    pub fn try_new(polynomial: u64) -> Result<Self, super::ChunkerError> {
+        Self::try_with_boundaries(
+            polynomial,
+            RESTIC_AVERAGE_BITS,
+            RESTIC_MIN_SIZE,
+            RESTIC_MAX_SIZE,
+        )
+    }
+
+    /// Constructs a new `ResticConfig` with custom chunk sizes.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if the polynomial is reducible or its degree is not between 8 and 56, or if any of
+    /// the size constraints are violated.
+    pub fn with_boundaries(
+        polynomial: u64,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_with_boundaries(polynomial, average_bits, min_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `ResticConfig` with custom chunk sizes, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `polynomial` - Irreducible polynomial over GF(2) of the restic repository.
+    /// * `average_bits` - Number of fingerprint bits that must be zero at a cut point.
+    /// * `min_size` - Minimum chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the polynomial is reducible or its degree is not between 8 and 56, or
+    /// if any of the size constraints are violated.
+    pub fn try_with_boundaries(
+        polynomial: u64,
+        average_bits: u32,
+        min_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        check_polynomial(polynomial)?;
+        let chunk_parms = restic_chunk_parms(average_bits, min_size, max_size)?;
+        Ok(Self {
+            tables: TablesRef::try_with_polynomial(polynomial, WINDOW_SIZE)?,
+            average_bits,
+            chunk_parms,
+        })
+    }
+
+    /// Returns the polynomial of the restic repository.
+    pub fn polynomial(&self) -> u64 {
+        self.tables.polynomial()
+    }
+}
+
+impl super::Chunker for ResticConfig {
+    type Iter<'a> = ResticCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Restic Rabin Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        let inner = RabinCDC::try_with_tables_ref(
+            source,
+            self.tables.clone(),
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+
+        ResticCDC {
+            inner: inner.with_cut_bits(self.average_bits),
+        }
+    }
+}
+
+impl Iterator for ResticCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Rabin fingerprint.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rabin_cdc::FP_POLY;
+    use crate::Chunker;
+
+    /// Tests the `chunks2` vector of the restic chunker tests, four chunks of the minimum size
+    /// cut from zeros with the polynomial of the restic tests.
+    #[test]
+    fn test_zeros() {
+        let data = vec![0u8; 4 * RESTIC_MIN_SIZE];
+        let chunks: Vec<_> = ResticConfig::new(FP_POLY).chunks(&data).collect();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk.length == RESTIC_MIN_SIZE));
+    }
+
+    /// Tests that the boundaries match the Rabin chunker with a 64-byte window.
+    ///
+    /// The one byte in the initial window is shifted out before the first cut check, so restic
+    /// cuts where a plain window fingerprint would.
+    #[test]
+    fn test_matches_rabin() {
+        let data = crate::generate_data_block(1024 * 1024, Some(163));
+        let restic: Vec<_> = ResticCDC::new(&data, FP_POLY, 12, 4096, 65536).collect();
+        let rabin: Vec<_> = crate::RabinCDC::new(&data, 64, 4096, 8192, 65536).collect();
+        assert_eq!(restic, rabin);
+    }
+
+    /// Tests that borrowed tables give the same boundaries, and that tables of another window
+    /// size are rejected.
+    #[test]
+    fn test_shared_tables() {
+        let data = crate::generate_data_block(1024 * 1024, Some(164));
+        let polynomial = crate::random_polynomial(164);
+        let tables = RabinTables::with_polynomial(polynomial, WINDOW_SIZE);
+        let shared: Vec<_> = ResticCDC::with_tables(&data, &tables, 12, 4096, 65536).collect();
+        let built: Vec<_> = ResticCDC::new(&data, polynomial, 12, 4096, 65536).collect();
+        assert_eq!(shared, built);
+
+        let tables = RabinTables::with_polynomial(polynomial, 32);
+        assert!(matches!(
+            ResticCDC::try_with_tables(&[], &tables, 12, 4096, 65536),
+            Err(crate::ChunkerError::WindowSize(32))
+        ));
+    }
+
+    /// Tests that the clones of a configuration share the tables built for its polynomial.
+    #[test]
+    fn test_config_tables() {
+        let polynomial = crate::random_polynomial(165);
+        let config = ResticConfig::with_boundaries(polynomial, 12, 4096, 65536);
+        assert_eq!(config.polynomial(), polynomial);
+        assert!(std::ptr::eq(&*config.clone().tables, &*config.tables));
+
+        let data = crate::generate_data_block(256 * 1024, Some(165));
+        let expected: Vec<_> = ResticCDC::new(&data, polynomial, 12, 4096, 65536).collect();
+        assert_eq!(config.chunks(&data).collect::<Vec<_>>(), expected);
+    }
+
+    /// Tests that the chunk sizes are checked together, including the derived average size.
+    #[test]
+    fn test_chunk_sizes() {
+        let config = ResticConfig::new(FP_POLY);
+        assert_eq!(
+            config.chunk_parms(),
+            crate::ChunkSizeParms::new(
+                RESTIC_MIN_SIZE,
+                RESTIC_MIN_SIZE + (1 << RESTIC_AVERAGE_BITS),
+                RESTIC_MAX_SIZE
+            )
+            .unwrap()
+        );
+
+        assert!(matches!(
+            ResticConfig::try_with_boundaries(FP_POLY, 20, 2 * RESTIC_MIN_SIZE, RESTIC_MIN_SIZE),
+            Err(crate::ChunkerError::UnorderedChunkSizes(_))
+        ));
+        assert!(matches!(
+            ResticCDC::try_new(&[], FP_POLY, 20, 2 * RESTIC_MIN_SIZE, RESTIC_MIN_SIZE),
+            Err(crate::ChunkerError::UnorderedChunkSizes(_))
+        ));
+        assert!(matches!(
+            ResticConfig::try_with_boundaries(FP_POLY, 1, 64, 1024),
+            Err(crate::ChunkerError::AvgChunkSize(66))
+        ));
+    }
+
+    /// Tests a polynomial of too high degree.
+    #[test]
+    #[should_panic(expected = "Polynomial degree out of valid range")]
+    fn test_polynomial_degree_too_high() {
+        let _chunker = ResticCDC::new(&[], 1 << 60, 20, RESTIC_MIN_SIZE, RESTIC_MAX_SIZE);
+    }
+}
@@ -0,0 +1,280 @@
+/// Minimum and maximum window sizes for chunking.
+const MIN_WIN_SIZE: usize = 8;
+const MAX_WIN_SIZE: usize = 8192;
+
+/// Modulus of the Adler-32 sums, the largest prime below 2^16.
+const ADLER_MOD: u32 = 65521;
+
+/// Multiplier scrambling the checksum before the cut mask is applied.
+const SCRAMBLE: u32 = 0x9e37_79b1;
+
+/// Rsync style chunker for data segmentation based on the Adler-32 rolling checksum.
+///
+/// The checksum is the Adler-32 of the sliding window, with the bytes before the chunk start
+/// taken as zeros. Adler-32 sums are not uniformly distributed in their low bits, so the checksum
+/// is scrambled by a multiplicative hash, and a chunk ends at the first position past the minimum
+/// chunk size where the high bits selected by the cut mask are zero, or at the maximum chunk size
+/// if no such position exists.
+pub struct RsyncCDC<'a> {
+    /// Size of the sliding window.
+    win_size: usize,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Mask used to determine chunk cuts.
+    cut_mask: u32,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> RsyncCDC<'a> {
+    /// Constructs a new `RsyncCDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `RsyncCDC` instance.
+    pub fn new(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Self {
+        Self::try_new(source, win_size, min_size, avg_size, max_size)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `RsyncCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `win_size` - Size of the sliding window for chunk determination.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        win_size: usize,
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        if !(MIN_WIN_SIZE..=MAX_WIN_SIZE).contains(&win_size) {
+            return Err(super::ChunkerError::WindowSize(win_size));
+        }
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        // Expected chunk length is the minimum size plus 2^bits.
+        let target = avg_size.saturating_sub(min_size).max(1);
+        let bits = (target as f64).log2().round() as u32;
+
+        Ok(Self {
+            win_size,
+            cur_pos: 0,
+            cut_mask: !(u32::MAX >> bits.min(31)),
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+        let first = min_size.saturating_sub(self.win_size);
+        let win_size = self.win_size as u32 % ADLER_MOD;
+
+        // Sums of a window of zeros.
+        let (mut a, mut b) = (1u32, win_size);
+        for (pos, &byte) in data.iter().enumerate().skip(first) {
+            let out_byte = if pos >= first + self.win_size {
+                u32::from(data[pos - self.win_size])
+            } else {
+                0
+            };
+
+            a = (a + ADLER_MOD - out_byte + u32::from(byte)) % ADLER_MOD;
+            b = (b + ADLER_MOD - win_size * out_byte % ADLER_MOD + a + ADLER_MOD - 1) % ADLER_MOD;
+
+            let checksum = (b << 16) | a;
+            if pos + 1 >= min_size && checksum.wrapping_mul(SCRAMBLE) & self.cut_mask == 0 {
+                return pos + 1;
+            }
+        }
+
+        limit
+    }
+}
+
+/// Configuration of the rsync chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RsyncConfig {
+    /// Size of the sliding window for chunk determination.
+    pub win_size: usize,
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl RsyncConfig {
+    /// Constructs a new `RsyncConfig`.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(win_size: usize, chunk_parms: super::ChunkSizeParms) -> Self {
+        Self {
+            win_size,
+            chunk_parms,
+        }
+    }
+
+    /// Constructs a new `RsyncConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `win_size` - Size of the sliding window for chunk determination
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        win_size: usize,
+        chunk_parms: super::ChunkSizeParms,
+    ) -> Result<Self, super::ChunkerError> {
+        RsyncCDC::try_new(
+            &[],
+            win_size,
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(win_size, chunk_parms))
+    }
+}
+
+impl super::Chunker for RsyncConfig {
+    type Iter<'a> = RsyncCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Rsync Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        RsyncCDC::new(
+            source,
+            self.win_size,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for RsyncCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the Adler-32 rolling checksum.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes the Adler-32 checksum of the data from scratch.
+    fn adler32(data: &[u8]) -> u32 {
+        let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+            let a = (a + u32::from(byte)) % ADLER_MOD;
+            (a, (b + a) % ADLER_MOD)
+        });
+        (b << 16) | a
+    }
+
+    /// Tests the checksum against the reference value of the Adler-32 specification.
+    #[test]
+    fn test_adler32_reference() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    /// Tests that the rolling checksum of every cut position matches the checksum of its window.
+    #[test]
+    fn test_cut_checksum_matches_window() {
+        let data = crate::generate_data_block(512 * 1024, Some(81));
+
+        for win_size in [8, 64, 700, 2048] {
+            let chunks: Vec<_> = RsyncCDC::new(&data, win_size, 1024, 2048, 8192).collect();
+            for chunk in &chunks[..chunks.len() - 1] {
+                if chunk.length == 8192 {
+                    continue;
+                }
+                let end = chunk.offset + chunk.length;
+                let mut window = vec![0u8; win_size.saturating_sub(chunk.length)];
+                window
+                    .extend_from_slice(&data[end.saturating_sub(win_size).max(chunk.offset)..end]);
+                let checksum = adler32(&window).wrapping_mul(SCRAMBLE);
+                assert_eq!(checksum >> 22, 0, "Window {} cut at {}", win_size, end);
+            }
+        }
+    }
+
+    /// Tests that the average chunk size of random data is close to the requested one.
+    #[test]
+    fn test_random_average() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(82));
+        let chunks: Vec<_> = RsyncCDC::new(&data, 64, 2048, 10240, 65536).collect();
+
+        let mut offset = 0;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 65536);
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+
+        let average = data.len() / chunks.len();
+        assert!((8192..=12288).contains(&average), "Average {}", average);
+    }
+}
@@ -0,0 +1,430 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read};
+
+use super::Chunker;
+
+/// Default size of the buffer the stream is read into.
+pub const DEFAULT_BUFFER_SIZE: usize = 4 * 1024 * 1024;
+
+/// Represents a chunk of a stream.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StreamChunk {
+    /// Starting byte position within the stream.
+    pub offset: u64,
+    /// Length of the chunk in bytes.
+    pub length: usize,
+}
+
+/// Represents a chunk of a stream together with its bytes.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ChunkData {
+    /// Starting byte position within the stream.
+    pub offset: u64,
+    /// Bytes of the chunk.
+    pub data: Vec<u8>,
+}
+
+/// Buffer holding the not yet emitted part of a stream and the chunks found in it.
+///
+/// The buffer is refilled by the caller, so it is shared by the blocking and the asynchronous
+/// stream chunkers. Every chunk found by the chunker is final, except the ones ending within the
+/// lookahead of the chunker before the end of the buffered data, which may still move once more
+/// data is read. These chunks are chunked again together with the next read, so the boundaries
+/// match chunking the whole stream at once.
+///
+/// The data isn't chunked before it holds more than the maximum chunk size plus the lookahead,
+/// which guarantees a final chunk, so every byte is searched about twice at most, even if the
+/// reads are short, like the ones of pipes and sockets.
+pub(crate) struct ChunkBuffer<C> {
+    /// Chunking algorithm configuration.
+    chunker: C,
+    /// Buffer the stream is read into.
+    buffer: Vec<u8>,
+    /// Position of the first byte in the buffer which is not part of an emitted chunk.
+    start: usize,
+    /// Number of valid bytes in the buffer.
+    filled: usize,
+    /// Stream offset of the byte at `start`.
+    offset: u64,
+    /// Final chunks not emitted yet, positioned relative to the buffer.
+    pending: VecDeque<super::Chunk>,
+    /// Whether the end of the stream is reached.
+    eof: bool,
+    /// Amount of data which always contains a final chunk.
+    window_size: usize,
+}
+
+impl<C: Chunker> ChunkBuffer<C> {
+    /// Constructs a new `ChunkBuffer`.
+    ///
+    /// The buffer is made larger than the maximum chunk size plus the lookahead, so a full buffer
+    /// always contains at least one final chunk.
+    pub(crate) fn new(chunker: C, buffer_size: usize) -> Self {
+        let window_size = chunker.chunk_parms().max_chunk_size + chunker.lookahead() + 1;
+        let buffer_size = buffer_size.max(window_size);
+
+        Self {
+            chunker,
+            buffer: vec![0u8; buffer_size],
+            start: 0,
+            filled: 0,
+            offset: 0,
+            pending: VecDeque::new(),
+            eof: false,
+            window_size,
+        }
+    }
+
+    /// Returns whether more data has to be read before the next chunk can be emitted.
+    pub(crate) fn needs_data(&self) -> bool {
+        self.pending.is_empty() && !self.eof
+    }
+
+    /// Returns the free part of the buffer to read the stream into.
+    ///
+    /// The data of not emitted chunks is moved to the beginning of the buffer beforehand.
+    pub(crate) fn spare(&mut self) -> &mut [u8] {
+        if self.start > 0 {
+            self.buffer.copy_within(self.start..self.filled, 0);
+            self.filled -= self.start;
+            self.start = 0;
+        }
+
+        &mut self.buffer[self.filled..]
+    }
+
+    /// Accounts for `len` bytes read into the spare part of the buffer and chunks them once they
+    /// hold a full window.
+    ///
+    /// A zero `len` marks the end of the stream.
+    pub(crate) fn commit(&mut self, len: usize) {
+        if len == 0 {
+            self.eof = true;
+        }
+        self.filled += len;
+        if !self.eof && self.filled - self.start < self.window_size {
+            return;
+        }
+
+        let data = &self.buffer[self.start..self.filled];
+        let eof = self.eof;
+        let start = self.start;
+        let lookahead = self.chunker.lookahead();
+        self.pending.extend(
+            self.chunker
+                .chunks(data)
+                .take_while(|chunk| eof || chunk.offset + chunk.length + lookahead < data.len())
+                .map(|chunk| super::Chunk {
+                    offset: start + chunk.offset,
+                    length: chunk.length,
+                }),
+        );
+    }
+
+    /// Pops the next final chunk along with its bytes.
+    pub(crate) fn pop(&mut self) -> Option<(StreamChunk, &[u8])> {
+        let chunk = self.pending.pop_front()?;
+
+        let stream_chunk = StreamChunk {
+            offset: self.offset,
+            length: chunk.length,
+        };
+        self.start = chunk.offset + chunk.length;
+        self.offset += chunk.length as u64;
+
+        Some((stream_chunk, &self.buffer[chunk.offset..self.start]))
+    }
+}
+
+/// Chunker reading its source from a [`Read`] instance.
+///
+/// The stream is read into a buffer of limited size, so the source never has to be loaded into
+/// memory at once, while the boundaries are the same as chunking the whole source in memory.
+pub struct StreamChunker<R, C> {
+    /// Reader providing the stream.
+    reader: R,
+    /// Buffer holding the not yet emitted part of the stream.
+    buffer: ChunkBuffer<C>,
+}
+
+impl<R: Read, C: Chunker> StreamChunker<R, C> {
+    /// Constructs a new `StreamChunker` with a buffer of [`DEFAULT_BUFFER_SIZE`] bytes.
+    ///
+    /// # Arguments
+    /// * `reader` - Reader providing the stream to be chunked.
+    /// * `chunker` - Chunking algorithm configuration.
+    ///
+    /// # Returns
+    ///
+    /// A new `StreamChunker` instance.
+    pub fn new(reader: R, chunker: C) -> Self {
+        Self::with_buffer_size(reader, chunker, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Constructs a new `StreamChunker` with a custom buffer size.
+    ///
+    /// # Arguments
+    /// * `reader` - Reader providing the stream to be chunked.
+    /// * `chunker` - Chunking algorithm configuration.
+    /// * `buffer_size` - Size of the read buffer. It is raised above the maximum chunk size plus
+    ///   the lookahead of the chunker if smaller.
+    ///
+    /// # Returns
+    ///
+    /// A new `StreamChunker` instance.
+    pub fn with_buffer_size(reader: R, chunker: C, buffer_size: usize) -> Self {
+        Self {
+            reader,
+            buffer: ChunkBuffer::new(chunker, buffer_size),
+        }
+    }
+
+    /// Computes the next chunk, borrowing its bytes from the internal buffer.
+    ///
+    /// # Returns
+    ///
+    /// Returns the next chunk and its bytes, which stay valid until the next call, `None` if an
+    /// end of stream is reached, or an error if reading the stream failed.
+    pub fn next_chunk(&mut self) -> Option<std::io::Result<(StreamChunk, &[u8])>> {
+        while self.buffer.needs_data() {
+            match self.reader.read(self.buffer.spare()) {
+                Ok(len) => self.buffer.commit(len),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        self.buffer.pop().map(Ok)
+    }
+
+    /// Converts the chunker into an iterator yielding the chunks with owned copies of their bytes.
+    pub fn with_data(self) -> StreamChunkerWithData<R, C> {
+        StreamChunkerWithData { inner: self }
+    }
+}
+
+impl<R: Read, C: Chunker> Iterator for StreamChunker<R, C> {
+    type Item = std::io::Result<StreamChunk>;
+
+    /// Computes the next chunk of the stream.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `StreamChunk` if available, `None` if an end of stream is reached, or an error
+    /// if reading the stream failed.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+            .map(|result| result.map(|(chunk, _)| chunk))
+    }
+}
+
+/// Iterator over the chunks of a stream along with owned copies of their bytes.
+pub struct StreamChunkerWithData<R, C> {
+    /// Chunker of the stream.
+    inner: StreamChunker<R, C>,
+}
+
+impl<R: Read, C: Chunker> Iterator for StreamChunkerWithData<R, C> {
+    type Item = std::io::Result<ChunkData>;
+
+    /// Computes the next chunk of the stream and copies its bytes.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `ChunkData` if available, `None` if an end of stream is reached, or an error if
+    /// reading the stream failed.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_chunk().map(|result| {
+            result.map(|(chunk, data)| ChunkData {
+                offset: chunk.offset,
+                data: data.to_vec(),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        ChunkSizeParms, FastCDCConfig, FixedSizeConfig, GearConfig, MaxpConfig, Normalization,
+    };
+
+    /// Chunk size parameters used by the stream tests.
+    const PARMS: ChunkSizeParms = ChunkSizeParms {
+        min_chunk_size: 1024,
+        avg_chunk_size: 4096,
+        max_chunk_size: 16384,
+    };
+
+    /// Reader returning at most `step` bytes per call.
+    struct SlowReader<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for SlowReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.step.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    /// Gear chunker counting the bytes it searches.
+    struct CountingChunker<'c> {
+        inner: GearConfig,
+        searched: &'c Cell<usize>,
+    }
+
+    impl Chunker for CountingChunker<'_> {
+        type Iter<'a> = crate::GearCDC<'a>;
+
+        fn name(&self) -> &'static str {
+            self.inner.name()
+        }
+
+        fn chunk_parms(&self) -> ChunkSizeParms {
+            self.inner.chunk_parms()
+        }
+
+        fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+            self.searched.set(self.searched.get() + source.len());
+            self.inner.chunks(source)
+        }
+    }
+
+    /// Reader failing on the first call.
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken"))
+        }
+    }
+
+    /// Checks that streaming yields the same chunks as chunking the data in memory.
+    fn assert_stream_matches<C: Chunker + Clone>(chunker: C, data: &[u8], buffer_size: usize) {
+        let expected: Vec<_> = chunker
+            .chunks(data)
+            .map(|chunk| StreamChunk {
+                offset: chunk.offset as u64,
+                length: chunk.length,
+            })
+            .collect();
+
+        let streamed =
+            StreamChunker::with_buffer_size(Cursor::new(data), chunker.clone(), buffer_size)
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap();
+        assert_eq!(streamed, expected, "Buffer size {}", buffer_size);
+
+        let reader = SlowReader { data, step: 777 };
+        let streamed = StreamChunker::with_buffer_size(reader, chunker, buffer_size)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            streamed, expected,
+            "Slow reader, buffer size {}",
+            buffer_size
+        );
+    }
+
+    /// Tests that the boundaries match the in-memory ones for various chunkers and buffer sizes.
+    #[test]
+    fn test_matches_in_memory() {
+        let data = crate::generate_data_block(1024 * 1024 + 123, Some(11));
+
+        for buffer_size in [0, 16385, 20000, 65536, 2 * 1024 * 1024] {
+            assert_stream_matches(FixedSizeConfig::new(5000), &data, buffer_size);
+            assert_stream_matches(GearConfig::new(PARMS), &data, buffer_size);
+            assert_stream_matches(
+                FastCDCConfig::new(PARMS, Normalization::Level2),
+                &data,
+                buffer_size,
+            );
+        }
+    }
+
+    /// Tests that the boundaries match the in-memory ones for a chunker with a lookahead.
+    #[test]
+    fn test_lookahead_matches_in_memory() {
+        let data = crate::generate_data_block(256 * 1024 + 45, Some(13));
+
+        for buffer_size in [0, 24000, 65536] {
+            assert_stream_matches(MaxpConfig::new(PARMS), &data, buffer_size);
+        }
+    }
+
+    /// Tests that reading a byte at a time gives the in-memory boundaries without searching the
+    /// data again on every read.
+    #[test]
+    fn test_single_byte_reads() {
+        let data = crate::generate_data_block(256 * 1024 + 31, Some(14));
+        let expected: Vec<_> = GearConfig::new(PARMS)
+            .chunks(&data)
+            .map(|chunk| StreamChunk {
+                offset: chunk.offset as u64,
+                length: chunk.length,
+            })
+            .collect();
+
+        let searched = Cell::new(0);
+        let chunker = CountingChunker {
+            inner: GearConfig::new(PARMS),
+            searched: &searched,
+        };
+        let reader = SlowReader {
+            data: &data,
+            step: 1,
+        };
+        let streamed = StreamChunker::with_buffer_size(reader, chunker, 0)
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(streamed, expected);
+        assert!(
+            searched.get() <= 3 * data.len(),
+            "Searched {} bytes",
+            searched.get()
+        );
+    }
+
+    /// Tests that the owned and borrowed bytes match the chunk positions.
+    #[test]
+    fn test_chunk_data() {
+        let data = crate::generate_data_block(300 * 1024, Some(12));
+
+        let owned = StreamChunker::with_buffer_size(Cursor::new(&data), GearConfig::new(PARMS), 0)
+            .with_data()
+            .collect::<std::io::Result<Vec<_>>>()
+            .unwrap();
+        let joined: Vec<u8> = owned.iter().flat_map(|chunk| chunk.data.clone()).collect();
+        assert_eq!(joined, data);
+
+        let mut chunker =
+            StreamChunker::with_buffer_size(Cursor::new(&data), GearConfig::new(PARMS), 0);
+        while let Some(result) = chunker.next_chunk() {
+            let (chunk, bytes) = result.unwrap();
+            let start = chunk.offset as usize;
+            assert_eq!(bytes, &data[start..start + chunk.length]);
+        }
+    }
+
+    /// Tests streaming of an empty source.
+    #[test]
+    fn test_empty_stream() {
+        let chunker = StreamChunker::new(Cursor::new(Vec::new()), GearConfig::new(PARMS));
+        assert_eq!(chunker.count(), 0);
+    }
+
+    /// Tests that read errors are surfaced.
+    #[test]
+    fn test_read_error() {
+        let mut chunker = StreamChunker::new(FailingReader, GearConfig::new(PARMS));
+        assert!(chunker.next().unwrap().is_err());
+    }
+}
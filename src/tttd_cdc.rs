@@ -0,0 +1,240 @@
+use super::fast_cdc::{GEAR, MASKS};
+
+/// Two Thresholds Two Divisors chunker for data segmentation based on the Gear rolling hash.
+///
+/// Besides the main divisor, which ends a chunk as soon as it matches past the minimum chunk size,
+/// an easier backup divisor remembers the last position it matched at. When the maximum chunk
+/// size is reached without a main cut, the chunk ends at that backup position instead, so forced
+/// cuts at the maximum chunk size only happen if neither divisor matched.
+pub struct TttdCDC<'a> {
+    /// Mask of the main divisor.
+    mask_main: u64,
+    /// Mask of the backup divisor, with one bit less than the main one.
+    mask_backup: u64,
+    /// Current position in data.
+    cur_pos: usize,
+    /// Data buffer to chunk.
+    source: &'a [u8],
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    chunk_parms: super::ChunkSizeParms,
+}
+
+impl<'a> TttdCDC<'a> {
+    /// Constructs a new `TttdCDC`.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Panics
+    ///
+    /// Panic if any of the size constraints are violated.
+    ///
+    /// # Returns
+    ///
+    /// A new `TttdCDC` instance.
+    pub fn new(source: &'a [u8], min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self::try_new(source, min_size, avg_size, max_size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new `TttdCDC`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `source` - Data buffer to be chunked.
+    /// * `min_size` - Minimum chunk size.
+    /// * `avg_size` - Average chunk size.
+    /// * `max_size` - Maximum chunk size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(
+        source: &'a [u8],
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    ) -> Result<Self, super::ChunkerError> {
+        let chunk_parms = super::ChunkSizeParms::new(min_size, avg_size, max_size)?;
+
+        // Expected chunk length is the minimum size plus the main divisor.
+        let target = avg_size.saturating_sub(min_size).max(1);
+        let bits = ((target as f64).log2().round() as usize).clamp(6, MASKS.len() - 1);
+
+        Ok(Self {
+            mask_main: MASKS[bits],
+            mask_backup: MASKS[bits - 1],
+            cur_pos: 0,
+            source,
+            chunk_parms,
+        })
+    }
+
+    /// Finds the length of the chunk at the start of `data`, see
+    /// [`Chunker::find_cut`](super::Chunker::find_cut).
+    ///
+    /// Hashing starts 64 bytes before the minimum chunk size, as earlier bytes are shifted out of
+    /// the hash by then.
+    pub fn find_cut(&self, data: &[u8]) -> usize {
+        let remaining = data.len();
+        let min_size = self.chunk_parms.min_chunk_size;
+        if remaining <= min_size {
+            return remaining;
+        }
+
+        let limit = remaining.min(self.chunk_parms.max_chunk_size);
+        let data = &data[..limit];
+
+        let mut hash = 0u64;
+        let mut backup = None;
+        for (pos, &byte) in data.iter().enumerate().skip(min_size.saturating_sub(64)) {
+            hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+            if pos + 1 < min_size {
+                continue;
+            }
+            if hash & self.mask_main == 0 {
+                return pos + 1;
+            }
+            if hash & self.mask_backup == 0 {
+                backup = Some(pos + 1);
+            }
+        }
+
+        // Only fall back to the backup divisor on a forced cut, not at the end of the data.
+        if limit < remaining {
+            backup.unwrap_or(limit)
+        } else {
+            limit
+        }
+    }
+}
+
+/// Configuration of the Two Thresholds Two Divisors chunking algorithm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TttdConfig {
+    /// Parameters specifying minimum, average, and maximum chunk sizes.
+    pub chunk_parms: super::ChunkSizeParms,
+}
+
+impl TttdConfig {
+    /// Constructs a new `TttdConfig`.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    pub fn new(chunk_parms: super::ChunkSizeParms) -> Self {
+        Self { chunk_parms }
+    }
+
+    /// Constructs a new `TttdConfig`, checking the parameters.
+    ///
+    /// # Arguments
+    /// * `chunk_parms` - Minimum, average, and maximum chunk sizes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the size constraints are violated.
+    pub fn try_new(chunk_parms: super::ChunkSizeParms) -> Result<Self, super::ChunkerError> {
+        TttdCDC::try_new(
+            &[],
+            chunk_parms.min_chunk_size,
+            chunk_parms.avg_chunk_size,
+            chunk_parms.max_chunk_size,
+        )?;
+        Ok(Self::new(chunk_parms))
+    }
+}
+
+impl super::Chunker for TttdConfig {
+    type Iter<'a> = TttdCDC<'a>;
+
+    fn name(&self) -> &'static str {
+        "Two Thresholds Two Divisors Content Defined Chunking"
+    }
+
+    fn chunk_parms(&self) -> super::ChunkSizeParms {
+        self.chunk_parms
+    }
+
+    fn chunks<'a>(&self, source: &'a [u8]) -> Self::Iter<'a> {
+        TttdCDC::new(
+            source,
+            self.chunk_parms.min_chunk_size,
+            self.chunk_parms.avg_chunk_size,
+            self.chunk_parms.max_chunk_size,
+        )
+    }
+}
+
+impl Iterator for TttdCDC<'_> {
+    type Item = super::Chunk;
+
+    /// Computes the next chunk based on the main and backup divisors.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Chunk` if the conditions for a chunk boundary are met, otherwise `None` if an
+    /// end of data is reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.source.len() - self.cur_pos;
+        if remaining == 0 {
+            return None;
+        }
+
+        let offset = self.cur_pos;
+        let length = self.find_cut(&self.source[offset..]);
+        self.cur_pos += length;
+
+        Some(super::Chunk { offset, length })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts the chunks of the maximum chunk size, leaving out the last one.
+    fn count_max_size(chunks: &[crate::Chunk], max_size: usize) -> usize {
+        chunks[..chunks.len() - 1]
+            .iter()
+            .filter(|chunk| chunk.length == max_size)
+            .count()
+    }
+
+    /// Tests that the backup divisor mostly avoids chunks of the maximum chunk size.
+    #[test]
+    fn test_backup_divisor_avoids_max_size() {
+        let data = crate::generate_data_block(4 * 1024 * 1024, Some(61));
+        let (min, avg, max) = (1024, 4096, 8192);
+
+        let tttd: Vec<_> = TttdCDC::new(&data, min, avg, max).collect();
+        let mut plain = TttdCDC::new(&data, min, avg, max);
+        plain.mask_backup = u64::MAX;
+        let plain: Vec<_> = plain.collect();
+
+        let (tttd_max, plain_max) = (count_max_size(&tttd, max), count_max_size(&plain, max));
+        assert!(plain_max > 20, "Plain max-size chunks {}", plain_max);
+        assert!(
+            tttd_max * 4 < plain_max,
+            "TTTD max-size chunks {} vs {}",
+            tttd_max,
+            plain_max
+        );
+    }
+
+    /// Tests chunk invariants on random data.
+    #[test]
+    fn test_random_data_invariants() {
+        let data = crate::generate_data_block(1024 * 1024 + 5, Some(62));
+        let chunks: Vec<_> = TttdCDC::new(&data, 2048, 8192, 32768).collect();
+
+        let mut offset = 0;
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.offset, offset);
+            assert!(chunk.length <= 32768);
+            assert!(chunk.length >= 2048 || i + 1 == chunks.len());
+            offset += chunk.length;
+        }
+        assert_eq!(offset, data.len());
+    }
+}
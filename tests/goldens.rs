@@ -0,0 +1,233 @@
+//! Golden chunk boundaries of every chunking algorithm.
+//!
+//! Each algorithm chunks the data generated from a few fixed seeds, and the offsets, lengths and
+//! blake3 hashes of the chunks are compared with the golden file of the algorithm in
+//! `tests/goldens`. Any change of a boundary fails the test of the algorithm.
+//!
+//! Run with `UPDATE_GOLDENS=1` to write the current chunks to the golden files instead, after
+//! deliberately changing the boundaries of an algorithm.
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use chunker_benches::{
+    generate_data_block, random_polynomial, AEConfig, BorgConfig, BorgParams, BuzhashConfig,
+    CasyncConfig, ChunkSizeParms, Chunker, FastCDCConfig, FastCDCVersion, FixedSizeConfig,
+    GearConfig, JumpConfig, MaxpConfig, Normalization, RabinConfig, RamConfig, ResticConfig,
+    RonomonConfig, RsyncConfig, TttdConfig, V2020Config, DEFAULT_BUZHASH_SEED,
+};
+
+/// Seeds of the data blocks chunked by every algorithm.
+const SEEDS: [u128; 2] = [0x601D_0001, 0x601D_0002];
+
+/// Size of the data blocks, not a multiple of any chunk size so that there is a tail.
+const DATA_SIZE: usize = 256 * 1024 + 123;
+
+/// Chunk sizes of the content defined chunkers.
+const PARMS: ChunkSizeParms = ChunkSizeParms {
+    min_chunk_size: 1024,
+    avg_chunk_size: 4096,
+    max_chunk_size: 16384,
+};
+
+/// Environment variable enabling the regeneration of the golden files.
+const UPDATE_VAR: &str = "UPDATE_GOLDENS";
+
+/// Renders the chunks of the data of every seed in the format of the golden files.
+///
+/// Lines starting with `#` describe the chunker and the data, every other line holds the offset,
+/// length and blake3 hash of a chunk.
+fn render<C: Chunker>(chunker: &C) -> String {
+    let mut out = format!("# {} ({})\n", chunker.name(), chunker.chunk_parms());
+    for seed in SEEDS {
+        let data = generate_data_block(DATA_SIZE, Some(seed));
+        writeln!(out, "# seed={:#x} size={}", seed, data.len()).unwrap();
+        for chunk in chunker.chunks(&data) {
+            let hash = blake3::hash(&data[chunk.offset..chunk.offset + chunk.length]);
+            writeln!(out, "{} {} {}", chunk.offset, chunk.length, hash).unwrap();
+        }
+    }
+    out
+}
+
+/// Compares the chunks of the chunker with the golden file of the given name, or overwrites the
+/// file when regeneration is enabled.
+///
+/// # Panics
+///
+/// Panic with the first differing line if the chunks drifted from the golden file.
+fn check_golden<C: Chunker>(name: &str, chunker: C) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "goldens", name]
+        .iter()
+        .collect::<PathBuf>()
+        .with_extension("golden");
+    let actual = render(&chunker);
+
+    if std::env::var_os(UPDATE_VAR).is_some_and(|value| value != "0") {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "Can't read golden file {}: {}, run with {}=1 to create it",
+            path.display(),
+            err,
+            UPDATE_VAR
+        )
+    });
+    if actual == expected {
+        return;
+    }
+
+    let (line, (expected_line, actual_line)) = expected
+        .lines()
+        .map(Some)
+        .chain(std::iter::repeat(None))
+        .zip(actual.lines().map(Some).chain(std::iter::repeat(None)))
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+        .unwrap();
+    panic!(
+        "Chunks drifted from golden file {} at line {}:\n  expected: {}\n  actual:   {}\n\
+         Run with {}=1 if the change is intended",
+        path.display(),
+        line + 1,
+        expected_line.unwrap_or("<end of file>"),
+        actual_line.unwrap_or("<end of file>"),
+        UPDATE_VAR
+    );
+}
+
+/// Tests the boundaries of the fixed size chunking.
+#[test]
+fn test_fixed_size() {
+    check_golden("fixed_size", FixedSizeConfig::new(PARMS.avg_chunk_size));
+}
+
+/// Tests the boundaries of the ronomon FastCDC adapter.
+#[test]
+fn test_ronomon() {
+    check_golden("ronomon", RonomonConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the 2020 FastCDC adapter.
+#[test]
+fn test_v2020() {
+    check_golden("v2020", V2020Config::new(PARMS));
+}
+
+/// Tests the boundaries of the Gear chunker.
+#[test]
+fn test_gear() {
+    check_golden("gear", GearConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the 2016 FastCDC algorithm.
+#[test]
+fn test_fast_cdc_2016() {
+    let config = FastCDCConfig {
+        version: FastCDCVersion::V2016,
+        ..FastCDCConfig::new(PARMS, Normalization::Level1)
+    };
+    check_golden("fast_cdc_2016", config);
+}
+
+/// Tests the boundaries of the 2020 FastCDC algorithm.
+#[test]
+fn test_fast_cdc_2020() {
+    check_golden(
+        "fast_cdc_2020",
+        FastCDCConfig::new(PARMS, Normalization::Level1),
+    );
+}
+
+/// Tests the boundaries of the Rabin chunker.
+#[test]
+fn test_rabin() {
+    check_golden("rabin", RabinConfig::new(64, PARMS));
+}
+
+/// Tests the boundaries of the Rabin chunker with a random polynomial, which also pins the
+/// polynomial generated from the seed.
+#[test]
+fn test_rabin_random_polynomial() {
+    let config = RabinConfig::with_polynomial(random_polynomial(0x601D), 64, PARMS);
+    check_golden("rabin_random_polynomial", config);
+}
+
+/// Tests the boundaries of the Buzhash chunker.
+#[test]
+fn test_buzhash() {
+    check_golden(
+        "buzhash",
+        BuzhashConfig::new(64, DEFAULT_BUZHASH_SEED, PARMS),
+    );
+}
+
+/// Tests the boundaries of the rsync chunker.
+#[test]
+fn test_rsync() {
+    check_golden("rsync", RsyncConfig::new(64, PARMS));
+}
+
+/// Tests the boundaries of the AE chunker.
+#[test]
+fn test_ae() {
+    check_golden("ae", AEConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the RAM chunker.
+#[test]
+fn test_ram() {
+    check_golden("ram", RamConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the TTTD chunker.
+#[test]
+fn test_tttd() {
+    check_golden("tttd", TttdConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the MAXP chunker.
+#[test]
+fn test_maxp() {
+    check_golden("maxp", MaxpConfig::new(PARMS));
+}
+
+/// Tests the boundaries of the jump-ahead chunker.
+#[test]
+fn test_jump() {
+    check_golden("jump", JumpConfig::new(1024, PARMS));
+}
+
+/// Tests the boundaries of the restic chunker.
+#[test]
+fn test_restic() {
+    let config = ResticConfig::with_boundaries(
+        0x3DA3358B4DC173,
+        12,
+        PARMS.min_chunk_size,
+        PARMS.max_chunk_size,
+    );
+    check_golden("restic", config);
+}
+
+/// Tests the boundaries of the borg chunker.
+#[test]
+fn test_borg() {
+    let params = BorgParams {
+        min_exp: 10,
+        max_exp: 14,
+        mask_bits: 12,
+        window_size: 255,
+    };
+    check_golden("borg", BorgConfig::new(0, params));
+}
+
+/// Tests the boundaries of the casync chunker.
+#[test]
+fn test_casync() {
+    check_golden("casync", CasyncConfig::new(PARMS));
+}
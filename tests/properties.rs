@@ -0,0 +1,385 @@
+//! Invariants every chunking algorithm has to satisfy, checked on randomized inputs and sizes.
+//!
+//! The chunkers are listed once in [`for_each_chunker`], so a new algorithm only has to be added
+//! there to run through all of the properties. A failure reports the seed of the case, which
+//! reproduces it when passed to [`run_case`].
+
+use rand::rngs::SmallRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+use chunker_benches::{
+    random_polynomial, AEConfig, BorgConfig, BorgParams, BuzhashConfig, CasyncConfig, Chunk,
+    ChunkSizeParms, Chunker, FastCDCConfig, FastCDCVersion, FixedSizeConfig, GearConfig,
+    IncrementalChunker, JumpConfig, MaxpConfig, Normalization, RabinConfig, RamConfig,
+    ResticConfig, RonomonConfig, RsyncConfig, StreamChunk, TttdConfig, V2020Config,
+};
+
+/// Number of randomized cases per kind of input.
+const CASES: u64 = 24;
+
+/// Polynomial of the restic chunker, the one of the restic test suite.
+const RESTIC_POLYNOMIAL: u64 = 0x3DA3358B4DC173;
+
+/// Receives each of the chunkers of a case.
+///
+/// The [`Chunker`] trait isn't object safe, so the chunkers are handed to a generic method
+/// instead of being collected into a list.
+trait ChunkerVisitor {
+    fn visit<C: Chunker + Clone>(&mut self, chunker: C);
+}
+
+/// Kind of data the chunkers are run on.
+#[derive(Debug, Clone, Copy)]
+enum Input {
+    /// Uniformly random bytes.
+    Random,
+    /// Bytes from a small alphabet, which makes rolling hashes repeat.
+    LowEntropy,
+    /// Random bytes interrupted by runs of zeros, which force cuts at the maximum size.
+    ZeroRuns,
+}
+
+/// Passes every chunker, configured with the given chunk sizes, to the visitor.
+///
+/// Parameters other than the chunk sizes are drawn from the random generator.
+fn for_each_chunker(parms: ChunkSizeParms, rng: &mut SmallRng, visitor: &mut impl ChunkerVisitor) {
+    let (min_size, avg_size, max_size) = (
+        parms.min_chunk_size,
+        parms.avg_chunk_size,
+        parms.max_chunk_size,
+    );
+
+    visitor.visit(FixedSizeConfig::new(avg_size));
+    visitor.visit(RonomonConfig::new(parms));
+    visitor.visit(V2020Config::new(parms));
+    visitor.visit(GearConfig::new(parms));
+    for version in [FastCDCVersion::V2016, FastCDCVersion::V2020] {
+        for level in [
+            Normalization::Level0,
+            Normalization::Level1,
+            Normalization::Level3,
+        ] {
+            let config = FastCDCConfig {
+                version,
+                ..FastCDCConfig::new(parms, level)
+            };
+            visitor.visit(config);
+            visitor.visit(FastCDCConfig {
+                vectorized: false,
+                ..config
+            });
+        }
+    }
+    visitor.visit(RabinConfig::new(1 << rng.gen_range(3..=6), parms));
+    visitor.visit(RabinConfig::with_polynomial(
+        random_polynomial(rng.gen()),
+        1 << rng.gen_range(3..=6),
+        parms,
+    ));
+    visitor.visit(BuzhashConfig::new(rng.gen_range(8..=256), rng.gen(), parms));
+    visitor.visit(RsyncConfig::new(rng.gen_range(8..=256), parms));
+    visitor.visit(AEConfig::new(parms));
+    visitor.visit(RamConfig::new(parms));
+    visitor.visit(TttdConfig::new(parms));
+    visitor.visit(MaxpConfig::new(parms));
+    if avg_size - min_size > 1 {
+        visitor.visit(JumpConfig::new(
+            rng.gen_range(1..avg_size - min_size),
+            parms,
+        ));
+    }
+    visitor.visit(CasyncConfig::new(parms));
+
+    // The minimum size plus 2^average_bits has to be a valid average size.
+    let average_bits = (avg_size - min_size).max(256).ilog2().min(30);
+    visitor.visit(ResticConfig::with_boundaries(
+        RESTIC_POLYNOMIAL,
+        average_bits,
+        min_size,
+        max_size,
+    ));
+
+    let min_exp = min_size.ilog2();
+    let max_exp = max_size.ilog2().max(10);
+    let params = BorgParams {
+        min_exp,
+        max_exp,
+        // The minimum size plus 2^mask_bits has to be a valid average size.
+        mask_bits: rng.gen_range(min_exp.max(8)..=max_exp),
+        window_size: rng.gen_range(3..=127) * 2 + 1,
+    };
+    visitor.visit(BorgConfig::new(rng.gen(), params));
+}
+
+/// Draws valid chunk sizes with `min <= avg <= max`.
+///
+/// Every eighth case has equal sizes where the valid ranges allow it, which leaves no room for
+/// content defined cuts.
+fn random_parms(rng: &mut SmallRng) -> ChunkSizeParms {
+    let (min_size, avg_size, max_size) = if rng.gen_ratio(1, 8) {
+        let size = rng.gen_range(1024..=4096);
+        (size, size, size)
+    } else {
+        let min_size = rng.gen_range(64..=2048);
+        let avg_size = rng.gen_range(min_size.max(256)..=min_size.max(256) * 8);
+        (
+            min_size,
+            avg_size,
+            rng.gen_range(avg_size.max(1024)..=avg_size * 4),
+        )
+    };
+    ChunkSizeParms::new(min_size, avg_size, max_size).unwrap()
+}
+
+/// Generates the data of a case.
+fn random_data(rng: &mut SmallRng, input: Input, len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    match input {
+        Input::Random => rng.fill_bytes(&mut data),
+        Input::LowEntropy => data.iter_mut().for_each(|byte| *byte = rng.gen_range(0..4)),
+        Input::ZeroRuns => {
+            rng.fill_bytes(&mut data);
+            for _ in 0..rng.gen_range(1..=4) {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                data[start..end].fill(0);
+            }
+        }
+    }
+    data
+}
+
+/// Checks the invariants of the chunks of `data`, splitting it at random positions drawn from
+/// `rng` for the incremental chunker.
+///
+/// # Returns
+///
+/// A description of the first violated invariant, if any.
+fn check_chunks<C: Chunker + Clone>(
+    chunker: &C,
+    data: &[u8],
+    rng: &mut SmallRng,
+) -> Result<(), String> {
+    let parms = chunker.chunk_parms();
+    let chunks: Vec<Chunk> = chunker.chunks(data).collect();
+
+    let mut pos = 0;
+    for (i, chunk) in chunks.iter().enumerate() {
+        if chunk.offset != pos {
+            return Err(format!(
+                "chunk {} starts at {} instead of {}",
+                i, chunk.offset, pos
+            ));
+        }
+        if chunk.length == 0 {
+            return Err(format!("chunk {} at {} is empty", i, chunk.offset));
+        }
+        if chunk.length > parms.max_chunk_size {
+            return Err(format!(
+                "chunk {} at {} has {} bytes, above the maximum {}",
+                i, chunk.offset, chunk.length, parms.max_chunk_size
+            ));
+        }
+        if chunk.length < parms.min_chunk_size && i + 1 != chunks.len() {
+            return Err(format!(
+                "chunk {} at {} has {} bytes, below the minimum {}",
+                i, chunk.offset, chunk.length, parms.min_chunk_size
+            ));
+        }
+        pos += chunk.length;
+    }
+    if pos != data.len() {
+        return Err(format!("chunks cover {} of {} bytes", pos, data.len()));
+    }
+
+    let again: Vec<Chunk> = chunker.chunks(data).collect();
+    if again != chunks {
+        return Err("chunking the same data twice gives different chunks".to_string());
+    }
+    check_find_cut(chunker, data, &chunks)?;
+    check_incremental(chunker.clone(), data, &chunks, rng)
+}
+
+/// Checks that the cut-point search finds the chunks when given the data following each
+/// boundary, and that a cut found in a truncated window is final as documented.
+///
+/// # Returns
+///
+/// A description of the first violated invariant, if any.
+fn check_find_cut<C: Chunker>(chunker: &C, data: &[u8], chunks: &[Chunk]) -> Result<(), String> {
+    let window_sizes = 2 * chunker.chunk_parms().max_chunk_size + chunker.lookahead();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let length = chunker.find_cut(&data[chunk.offset..]);
+        if length != chunk.length {
+            return Err(format!(
+                "search at {} finds {} bytes instead of {}",
+                chunk.offset, length, chunk.length
+            ));
+        }
+
+        let end = data.len().min(chunk.offset + i * 7919 % window_sizes);
+        let window = &data[chunk.offset..end];
+        let length = chunker.find_cut(window);
+        if length > window.len()
+            || length + chunker.lookahead() < window.len() && length != chunk.length
+        {
+            return Err(format!(
+                "search at {} in a window of {} bytes finds {} bytes instead of {}",
+                chunk.offset,
+                window.len(),
+                length,
+                chunk.length
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that feeding the data to the incremental chunker in fragments of random sizes gives
+/// the same chunks.
+///
+/// # Returns
+///
+/// A description of the first violated invariant, if any.
+fn check_incremental<C: Chunker>(
+    chunker: C,
+    data: &[u8],
+    chunks: &[Chunk],
+    rng: &mut SmallRng,
+) -> Result<(), String> {
+    let max_size = chunker.chunk_parms().max_chunk_size;
+    let mut incremental = IncrementalChunker::new(chunker);
+    let mut fed = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let max_len = [16, max_size, 4 * max_size][rng.gen_range(0..3)];
+        let (fragment, tail) = rest.split_at(rng.gen_range(0..=max_len).min(rest.len()));
+        fed.extend(incremental.update(fragment));
+        rest = tail;
+    }
+    fed.extend(incremental.finish());
+
+    let expected: Vec<StreamChunk> = chunks
+        .iter()
+        .map(|chunk| StreamChunk {
+            offset: chunk.offset as u64,
+            length: chunk.length,
+        })
+        .collect();
+    match fed
+        .iter()
+        .zip(&expected)
+        .find(|(fed, expected)| fed != expected)
+    {
+        Some((fed, expected)) => Err(format!(
+            "incremental chunk at {} has {} bytes instead of {} at {}",
+            fed.offset, fed.length, expected.length, expected.offset
+        )),
+        None if fed.len() != expected.len() => Err(format!(
+            "incremental chunking gives {} chunks instead of {}",
+            fed.len(),
+            expected.len()
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Visitor checking the invariants of every chunker on the same data.
+struct PropertyCheck<'a> {
+    /// Data the chunkers are run on.
+    data: &'a [u8],
+    /// Generator of the fragment sizes for the incremental chunker.
+    rng: SmallRng,
+    /// Violations found, with the name of the chunker.
+    failures: Vec<String>,
+}
+
+impl ChunkerVisitor for PropertyCheck<'_> {
+    fn visit<C: Chunker + Clone>(&mut self, chunker: C) {
+        if let Err(failure) = check_chunks(&chunker, self.data, &mut self.rng) {
+            self.failures.push(format!(
+                "{} ({}): {}",
+                chunker.name(),
+                chunker.chunk_parms(),
+                failure
+            ));
+        }
+    }
+}
+
+/// Runs every chunker on the data of the case with the given seed.
+///
+/// The length of the data is drawn around the chunk sizes, or taken from `len` when given.
+///
+/// # Panics
+///
+/// Panic with the seed and all violations if any invariant doesn't hold.
+fn run_case(seed: u64, input: Input, len: Option<usize>) {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let parms = random_parms(&mut rng);
+    let len = len.unwrap_or_else(|| rng.gen_range(0..=parms.max_chunk_size * 4));
+    let data = random_data(&mut rng, input, len);
+
+    let mut check = PropertyCheck {
+        data: &data,
+        rng: SmallRng::seed_from_u64(rng.gen()),
+        failures: Vec::new(),
+    };
+    for_each_chunker(parms, &mut rng, &mut check);
+
+    assert!(
+        check.failures.is_empty(),
+        "Case with seed {} ({:?}, {} bytes):\n{}",
+        seed,
+        input,
+        data.len(),
+        check.failures.join("\n")
+    );
+}
+
+/// Tests the invariants on random data.
+#[test]
+fn test_random_data() {
+    for seed in 0..CASES {
+        run_case(seed, Input::Random, None);
+    }
+}
+
+/// Tests the invariants on data from a small alphabet.
+#[test]
+fn test_low_entropy_data() {
+    for seed in 1000..1000 + CASES {
+        run_case(seed, Input::LowEntropy, None);
+    }
+}
+
+/// Tests the invariants on random data with runs of zeros.
+#[test]
+fn test_zero_runs() {
+    for seed in 2000..2000 + CASES {
+        run_case(seed, Input::ZeroRuns, None);
+    }
+}
+
+/// Tests the invariants on lengths around the chunk sizes, where off-by-one errors show up.
+#[test]
+fn test_edge_lengths() {
+    for seed in 3000..3000 + CASES {
+        let parms = random_parms(&mut SmallRng::seed_from_u64(seed));
+        let (min_size, max_size) = (parms.min_chunk_size, parms.max_chunk_size);
+        for len in [
+            0,
+            1,
+            min_size - 1,
+            min_size,
+            min_size + 1,
+            max_size - 1,
+            max_size,
+            max_size + 1,
+            max_size + min_size,
+            2 * max_size,
+        ] {
+            run_case(seed, Input::Random, Some(len));
+        }
+    }
+}